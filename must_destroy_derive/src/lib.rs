@@ -0,0 +1,243 @@
+//! # must_destroy_derive
+//!
+//! Provides `#[derive(Destroy)]` for `must_destroy`. Use it through the `derive` feature of
+//! `must_destroy` rather than depending on this crate directly.
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Expr, Fields, Ident, LitInt, Type};
+
+/// Derives `Destroy` for a struct or enum by destroying its fields.
+///
+/// Fields of type `MustDestroy<..>`, and fields marked `#[destroy]`, are destroyed in
+/// declaration order. All other fields are simply dropped afterwards, also in declaration
+/// order.
+///
+/// The type's `Args` defaults to `()`, and can be set with `#[destroy(args = Type)]` on the
+/// type. Each destroyed field is given a clone of the arguments by default, which can be
+/// changed with `#[destroy(with = expr)]`, where `expr` can refer to the arguments as
+/// `args`.
+///
+/// Fields marked `#[destroy(order = N)]` are destroyed first, in ascending order of `N`,
+/// before the rest in declaration order. `#[destroy(skip)]` takes the item out of a
+/// `MustDestroy` field with `into_inner` and drops it with the other fields, instead of
+/// destroying it.
+///
+/// No bounds are added for the type's generic parameters, as there's no telling which
+/// arguments they'd be destroyed with. A parameter used by a destroyed field needs its
+/// `Destroy` bound on the type itself, such as `struct Wrapper<T: Destroy<()>>`.
+///
+/// ```ignore
+/// #[derive(Destroy)]
+/// #[destroy(args = (&'static str, u32))]
+/// struct Renderer {
+///     #[destroy(with = args.1)]
+///     buffer: MustDestroy<Buffer>,
+///     #[destroy(order = 0, with = args.0)]
+///     device: Device,
+///     frames: u64,
+/// }
+/// ```
+#[proc_macro_derive(Destroy, attributes(destroy))]
+pub fn derive_destroy(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let args_ty = container_args(&input.attrs)?;
+    let name = &input.ident;
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, destroys) = destroy_fields(quote!(#name), &data.fields)?;
+            quote! {
+                let #pattern = self;
+                #destroys
+            }
+        }
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let (pattern, destroys) = destroy_fields(quote!(#name::#ident), &variant.fields)?;
+                arms.push(quote! {
+                    #pattern => { #destroys }
+                });
+            }
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span,
+                "`Destroy` can not be derived for unions",
+            ))
+        }
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::must_destroy::Destroy<#args_ty> for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn destroy(self, args: #args_ty) {
+                #body
+            }
+        }
+    })
+}
+
+/// What happens to a single field.
+enum FieldUse {
+    Destroyed(Box<FieldDestroy>),
+    Dropped,
+    /// A skipped `MustDestroy`, whose item is taken out so the guard isn't dropped.
+    Disarmed,
+}
+
+/// How a single destroyed field takes part in destruction.
+struct FieldDestroy {
+    binding: Ident,
+    with: Option<Expr>,
+    order: Option<u64>,
+}
+
+/// Builds the pattern binding the destroyed fields, and the statements destroying them.
+fn destroy_fields(
+    path: TokenStream2,
+    fields: &Fields,
+) -> syn::Result<(TokenStream2, TokenStream2)> {
+    let mut bindings = Vec::new();
+    let mut destroyed = Vec::new();
+    let mut dropped = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let binding = format_ident!("__destroy_field_{}", index);
+        let member = match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = syn::Index::from(index);
+                quote!(#index)
+            }
+        };
+        bindings.push(quote!(#member: #binding));
+        match field_use(field, binding.clone())? {
+            FieldUse::Destroyed(destroy) => destroyed.push(*destroy),
+            FieldUse::Dropped => dropped.push(quote!(#binding)),
+            FieldUse::Disarmed => {
+                dropped.push(quote!(::must_destroy::MustDestroy::into_inner(#binding)))
+            }
+        }
+    }
+
+    // Explicitly ordered fields go first, the rest keep their declaration order.
+    destroyed.sort_by_key(|destroy| destroy.order.unwrap_or(u64::MAX));
+
+    let last = destroyed.len().saturating_sub(1);
+    let destroys = destroyed.iter().enumerate().map(|(position, destroy)| {
+        let binding = &destroy.binding;
+        let args = match &destroy.with {
+            Some(with) => quote!(#with),
+            // The last field can take the arguments themselves, rather than a clone.
+            None if position == last => quote!(args),
+            None => quote!(::core::clone::Clone::clone(&args)),
+        };
+        quote! {
+            ::must_destroy::Destroy::destroy(#binding, #args);
+        }
+    });
+
+    Ok((
+        quote!(#path { #(#bindings,)* }),
+        quote! {
+            #(#destroys)*
+            #(::core::mem::drop(#dropped);)*
+        },
+    ))
+}
+
+/// Reads the field's `#[destroy(..)]` attributes.
+fn field_use(field: &syn::Field, binding: Ident) -> syn::Result<FieldUse> {
+    let must_destroy = is_must_destroy(&field.ty);
+    let mut destroy = must_destroy;
+    let mut skip = false;
+    let mut with = None;
+    let mut order = None;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("destroy"))
+    {
+        destroy = true;
+        if let syn::Meta::Path(_) = attr.meta {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+            } else if meta.path.is_ident("with") {
+                with = Some(meta.value()?.parse::<Expr>()?);
+            } else if meta.path.is_ident("order") {
+                order = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+            } else {
+                return Err(meta.error("expected `skip`, `with` or `order`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    if skip && (with.is_some() || order.is_some()) {
+        return Err(syn::Error::new(
+            field.span(),
+            "a skipped field can not have `with` or `order`",
+        ));
+    }
+
+    Ok(if skip && must_destroy {
+        FieldUse::Disarmed
+    } else if destroy && !skip {
+        FieldUse::Destroyed(Box::new(FieldDestroy {
+            binding,
+            with,
+            order,
+        }))
+    } else {
+        FieldUse::Dropped
+    })
+}
+
+/// Reads the `Args` type from the container's `#[destroy(args = Type)]`, defaulting to `()`.
+fn container_args(attrs: &[Attribute]) -> syn::Result<Type> {
+    let mut args = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("destroy")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("args") {
+                args = Some(meta.value()?.parse::<Type>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `args`"))
+            }
+        })?;
+    }
+    Ok(args.unwrap_or_else(|| syn::parse_quote!(())))
+}
+
+/// Whether the type is spelled as a `MustDestroy<..>`.
+fn is_must_destroy(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "MustDestroy"),
+        _ => false,
+    }
+}
@@ -0,0 +1,251 @@
+//! A generational arena of items that must be destroyed, addressed by typed handles.
+use crate::held::Held;
+use crate::impls::destroy_each;
+use crate::origin::Origin;
+use crate::{DefaultPolicy, Destroy, DropPolicy, MustDestroy};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// Addresses an item in a `DestroyArena`.
+///
+/// Once the item is removed, the handle goes stale and no longer finds anything, even if
+/// the item's slot is reused.
+pub struct ArenaHandle<T> {
+    index: u32,
+    generation: u32,
+    item: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for ArenaHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ArenaHandle<T> {}
+
+impl<T> PartialEq for ArenaHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for ArenaHandle<T> {}
+
+impl<T> Hash for ArenaHandle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for ArenaHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArenaHandle")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+struct Slot<T, P: DropPolicy> {
+    generation: u32,
+    item: Option<Held<T, P>>,
+}
+
+/// Owns items that must be destroyed, handing out `Copy` handles to them.
+///
+/// Removing an item hands it back as an armed guard. The arena itself must be destroyed,
+/// like a [`MustDestroy`].
+pub struct DestroyArena<T, P: DropPolicy = DefaultPolicy> {
+    slots: Vec<Slot<T, P>>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+impl<T> DestroyArena<T> {
+    /// Create a new, empty `DestroyArena`
+    pub fn new() -> Self {
+        DestroyArena::with_policy(DefaultPolicy)
+    }
+}
+
+impl<T> Default for DestroyArena<T> {
+    fn default() -> Self {
+        DestroyArena::new()
+    }
+}
+
+impl<T, P: DropPolicy> DestroyArena<T, P> {
+    /// Create a new, empty `DestroyArena`, whose items use `policy` if they're dropped
+    pub fn with_policy(_policy: P) -> Self {
+        DestroyArena {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// The number of items in the arena.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the arena has no items.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts an item, recording the caller as where its guard was created.
+    #[track_caller]
+    pub fn insert(&mut self, item: T) -> ArenaHandle<T> {
+        self.insert_guard(MustDestroy::arm(item, Origin::here::<T>()))
+    }
+
+    /// Inserts an item that is already guarded.
+    pub fn insert_guard(&mut self, guard: MustDestroy<T, P>) -> ArenaHandle<T> {
+        let item = Some(Held::new(guard));
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index as usize].item = item;
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    item,
+                });
+                (self.slots.len() - 1) as u32
+            }
+        };
+        self.len += 1;
+        ArenaHandle {
+            index,
+            generation: self.slots[index as usize].generation,
+            item: PhantomData,
+        }
+    }
+
+    fn slot(&self, handle: ArenaHandle<T>) -> Option<&Slot<T, P>> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+    }
+
+    fn slot_mut(&mut self, handle: ArenaHandle<T>) -> Option<&mut Slot<T, P>> {
+        self.slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+    }
+
+    /// Whether `handle` still addresses an item.
+    pub fn contains(&self, handle: ArenaHandle<T>) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// The item addressed by `handle`, if it hasn't been removed.
+    pub fn get(&self, handle: ArenaHandle<T>) -> Option<&T> {
+        self.slot(handle)?.item.as_ref().map(Held::get)
+    }
+
+    /// The item addressed by `handle`, if it hasn't been removed.
+    pub fn get_mut(&mut self, handle: ArenaHandle<T>) -> Option<&mut T> {
+        self.slot_mut(handle)?.item.as_mut().map(Held::get_mut)
+    }
+
+    /// Removes the item addressed by `handle`, handing it back still armed. Returns `None`
+    /// if the handle is stale.
+    pub fn remove(&mut self, handle: ArenaHandle<T>) -> Option<MustDestroy<T, P>> {
+        let slot = self.slot_mut(handle)?;
+        let item = slot.item.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        self.len -= 1;
+        Some(item.into_guard())
+    }
+
+    /// Iterates over the items, along with their handles, in slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (ArenaHandle<T>, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let item = slot.item.as_ref()?;
+            let handle = ArenaHandle {
+                index: index as u32,
+                generation: slot.generation,
+                item: PhantomData,
+            };
+            Some((handle, item.get()))
+        })
+    }
+
+    /// Destroys every item in slot order, with a clone of `args`, consuming the arena.
+    pub fn destroy_all<Args: Clone>(self, args: Args)
+    where
+        T: Destroy<Args>,
+    {
+        destroy_each(self.slots.into_iter().filter_map(|slot| slot.item), args);
+    }
+}
+
+impl<Args: Clone, T: Destroy<Args>, P: DropPolicy> Destroy<Args> for DestroyArena<T, P> {
+    /// destroys every item in slot order, with a clone of the arguments
+    fn destroy(self, args: Args) {
+        self.destroy_all(args);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{scope, Destroy, DestroyArena, Reason};
+    use std::cell::RefCell;
+
+    /// Records its id to the log when destroyed.
+    struct Texture<'a>(u32, &'a RefCell<Vec<u32>>);
+
+    impl<'a> Destroy<()> for Texture<'a> {
+        fn destroy(self, _args: ()) {
+            self.1.borrow_mut().push(self.0);
+        }
+    }
+
+    #[test]
+    fn test_arena() {
+        let log = RefCell::new(Vec::new());
+        let mut textures = DestroyArena::new();
+        let first = textures.insert(Texture(1, &log));
+        let second = textures.insert(Texture(2, &log));
+        assert_eq!(2, textures.len());
+        assert_eq!(Some(1), textures.get(first).map(|texture| texture.0));
+
+        textures.remove(first).unwrap().destroy();
+        assert!(!textures.contains(first));
+        assert!(textures.remove(first).is_none());
+
+        // The freed slot is reused, but the stale handle doesn't find the new item.
+        let third = textures.insert(Texture(3, &log));
+        assert_ne!(first, third);
+        assert!(textures.get(first).is_none());
+        assert_eq!(Some(3), textures.get(third).map(|texture| texture.0));
+
+        let handles: Vec<_> = textures.iter().map(|(handle, _)| handle).collect();
+        assert_eq!(vec![third, second], handles);
+
+        textures.destroy_all(());
+        assert_eq!(vec![1, 3, 2], log.into_inner());
+    }
+
+    #[test]
+    fn test_dropping_non_empty_arena_is_violation() {
+        let log = RefCell::new(Vec::new());
+        let violations = scope(|s| {
+            let mut textures = DestroyArena::new();
+            textures.insert_guard(s.guard(Texture(1, &log)));
+            let removed = textures.insert_guard(s.guard(Texture(2, &log)));
+            textures.remove(removed).unwrap().destroy();
+        })
+        .unwrap_err();
+        assert_eq!(1, violations.len());
+        assert_eq!(Reason::Dropped, violations[0].reason());
+        assert_eq!(vec![2], log.into_inner());
+    }
+}
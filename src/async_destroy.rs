@@ -0,0 +1,158 @@
+//! Destruction that needs to `.await`, without tying the crate to any executor.
+use crate::origin::Origin;
+use crate::{DefaultPolicy, DropPolicy, MustDestroy, Reason, Violation};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Trait applied to items that are destroyed asynchronously.
+///
+/// `Args` works the same as it does for `Destroy`.
+pub trait AsyncDestroy<Args> {
+    /// The future that destroys the item when driven to completion.
+    type Future: Future<Output = ()>;
+
+    /// Starts destroying the item being called upon. It is only destroyed once the
+    /// returned future completes.
+    fn destroy_async(self, args: Args) -> Self::Future;
+}
+
+/// Future returned by `MustDestroy::destroy_async`.
+///
+/// The obligation to destroy stays armed until this completes, so dropping it early is a
+/// violation handled by the guard's `DropPolicy`. Since the inner future may be pinned, it
+/// is always dropped, whatever the policy's `Disposal`.
+#[must_use = "the wrapped item is only destroyed once this future completes"]
+pub struct DestroyFuture<T, F, P: DropPolicy = DefaultPolicy> {
+    future: F,
+    done: bool,
+    origin: Origin,
+    wrapped: PhantomData<fn() -> (T, P)>,
+}
+
+impl<T, F: Future<Output = ()>, P: DropPolicy> Future for DestroyFuture<T, F, P> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // Safe because `future` is never moved out of, not even by our `Drop`, and `done`
+        // isn't structurally pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        let poll = future.poll(cx);
+        if poll.is_ready() {
+            this.done = true;
+        }
+        poll
+    }
+}
+
+impl<T, F, P: DropPolicy> Drop for DestroyFuture<T, F, P> {
+    fn drop(&mut self) {
+        if !self.done {
+            let violation = Violation::new(&self.origin, Reason::Cancelled);
+            self.origin.report::<P>(violation);
+        }
+    }
+}
+
+impl<T, P: DropPolicy> MustDestroy<T, P> {
+    /// Starts destroying and consuming self and wrapped child, which is done once the
+    /// returned future completes.
+    pub fn destroy_async<Args>(self, args: Args) -> DestroyFuture<T, T::Future, P>
+    where
+        T: AsyncDestroy<Args>,
+    {
+        let (wrapped, origin, _) = self.disarm();
+        DestroyFuture {
+            future: wrapped.destroy_async(args),
+            done: false,
+            origin,
+            wrapped: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AsyncDestroy, LeakPolicy, MustDestroy};
+    use std::cell::Cell;
+    use std::future::Future;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::pin::Pin;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    fn noop_waker() -> Waker {
+        fn clone(_: *const ()) -> RawWaker {
+            RawWaker::new(std::ptr::null(), &VTABLE)
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        // Safe because the vtable functions do nothing with the data pointer.
+        unsafe { Waker::from_raw(clone(std::ptr::null())) }
+    }
+
+    /// Polls `future` to completion on the current thread.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Returns `Pending` once before completing.
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    struct Connection<'a> {
+        goodbye: &'a Cell<Option<&'static str>>,
+    }
+
+    impl<'a> AsyncDestroy<&'static str> for Connection<'a> {
+        type Future = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+        fn destroy_async(self, goodbye: &'static str) -> Self::Future {
+            Box::pin(async move {
+                YieldNow(false).await;
+                self.goodbye.set(Some(goodbye));
+            })
+        }
+    }
+
+    #[test]
+    fn test_destroy_async() {
+        let goodbye = Cell::new(None);
+        let guard = MustDestroy::new(Connection { goodbye: &goodbye });
+        block_on(guard.destroy_async("bye"));
+        assert_eq!(Some("bye"), goodbye.get());
+    }
+
+    #[test]
+    fn test_dropping_unfinished_destroy_is_violation() {
+        let goodbye = Cell::new(None);
+        let guard = MustDestroy::new(Connection { goodbye: &goodbye });
+        let future = guard.destroy_async("bye");
+        assert!(catch_unwind(AssertUnwindSafe(move || drop(future))).is_err());
+        assert_eq!(None, goodbye.get());
+
+        let guard = MustDestroy::with_policy(Connection { goodbye: &goodbye }, LeakPolicy);
+        drop(guard.destroy_async("bye"));
+        assert_eq!(None, goodbye.get());
+    }
+}
@@ -0,0 +1,201 @@
+//! A container owning guards of many different types, destroyed together.
+use crate::held::Held;
+use crate::impls::destroy_each;
+use crate::origin::Origin;
+use crate::{DefaultPolicy, Destroy, DropPolicy, DynDestroy, MustDestroy};
+use std::fmt;
+
+/// The order a `DestroyBag` destroys its guards in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DestroyOrder {
+    /// The most recently added guard is destroyed first, like local variables are dropped.
+    Lifo,
+    /// The guards are destroyed in the order they were added.
+    Fifo,
+}
+
+struct Entry<'a, Args, P: DropPolicy> {
+    type_name: &'static str,
+    item: Held<Box<dyn DynDestroy<Args> + 'a>, P>,
+}
+
+/// Owns guards of any type taking the same `Args`, to destroy them all with one call.
+///
+/// Each guard keeps the name of its original type, which is what violations and `Debug`
+/// report. The bag must be destroyed, just like the guards it holds.
+pub struct DestroyBag<'a, Args, P: DropPolicy = DefaultPolicy> {
+    entries: Vec<Entry<'a, Args, P>>,
+    order: DestroyOrder,
+}
+
+impl<'a, Args> DestroyBag<'a, Args> {
+    /// Create a new, empty `DestroyBag`, destroying its guards in `DestroyOrder::Lifo`
+    pub fn new() -> Self {
+        DestroyBag::with_policy(DefaultPolicy)
+    }
+}
+
+impl<'a, Args> Default for DestroyBag<'a, Args> {
+    fn default() -> Self {
+        DestroyBag::new()
+    }
+}
+
+impl<'a, Args, P: DropPolicy> DestroyBag<'a, Args, P> {
+    /// Create a new, empty `DestroyBag` for guards using `policy`, destroying them in
+    /// `DestroyOrder::Lifo`
+    pub fn with_policy(_policy: P) -> Self {
+        DestroyBag {
+            entries: Vec::new(),
+            order: DestroyOrder::Lifo,
+        }
+    }
+
+    /// The order the guards will be destroyed in.
+    pub fn order(&self) -> DestroyOrder {
+        self.order
+    }
+
+    /// Changes the order the guards will be destroyed in.
+    pub fn set_order(&mut self, order: DestroyOrder) {
+        self.order = order;
+    }
+
+    /// The number of guards in the bag.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the bag holds no guards.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds an item, recording the caller as where its guard was created.
+    #[track_caller]
+    pub fn add<T: Destroy<Args> + 'a>(&mut self, item: T) {
+        self.push(MustDestroy::arm(item, Origin::here::<T>()));
+    }
+
+    /// Adds an item that is already guarded.
+    pub fn push<T: Destroy<Args> + 'a>(&mut self, guard: MustDestroy<T, P>) {
+        let (item, origin, fallback) = guard.disarm();
+        let item: Box<dyn DynDestroy<Args> + 'a> = Box::new(item);
+        // Safe because the box holds the item the fallback was made for.
+        let fallback =
+            fallback.map(|fallback| unsafe { fallback.boxed::<T, dyn DynDestroy<Args> + 'a>() });
+        self.entries.push(Entry {
+            type_name: origin.type_name(),
+            item: Held::new(MustDestroy::rearm(item, origin, fallback)),
+        });
+    }
+
+    /// The names of the types of the guards, in the order they were added.
+    pub fn type_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries.iter().map(|entry| entry.type_name)
+    }
+
+    /// Destroys every guard in the bag's order, with a clone of `args`, consuming the bag.
+    pub fn destroy_all(self, args: Args)
+    where
+        Args: Clone,
+    {
+        let items = self.entries.into_iter().map(|entry| entry.item);
+        match self.order {
+            DestroyOrder::Lifo => destroy_each(items.rev(), args),
+            DestroyOrder::Fifo => destroy_each(items, args),
+        }
+    }
+}
+
+impl<'a, Args: Clone, P: DropPolicy> Destroy<Args> for DestroyBag<'a, Args, P> {
+    /// destroys every guard in the bag's order, with a clone of the arguments
+    fn destroy(self, args: Args) {
+        self.destroy_all(args);
+    }
+}
+
+impl<'a, Args, P: DropPolicy> fmt::Debug for DestroyBag<'a, Args, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DestroyBag")
+            .field("order", &self.order)
+            .field("entries", &self.type_names().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{scope, Destroy, DestroyBag, DestroyOrder, MustDestroy, Reason};
+    use std::cell::RefCell;
+
+    /// Records its name to the log when destroyed.
+    struct Window<'a>(&'a RefCell<Vec<&'static str>>);
+
+    impl<'a> Destroy<()> for Window<'a> {
+        fn destroy(self, _args: ()) {
+            self.0.borrow_mut().push("window");
+        }
+    }
+
+    /// Records its name to the log when destroyed.
+    struct Surface<'a>(&'a RefCell<Vec<&'static str>>);
+
+    impl<'a> Destroy<()> for Surface<'a> {
+        fn destroy(self, _args: ()) {
+            self.0.borrow_mut().push("surface");
+        }
+    }
+
+    fn fill<'a>(log: &'a RefCell<Vec<&'static str>>, order: DestroyOrder) -> DestroyBag<'a, ()> {
+        let mut bag = DestroyBag::new();
+        bag.set_order(order);
+        bag.add(Window(log));
+        bag.push(MustDestroy::new(Surface(log)));
+        bag
+    }
+
+    #[test]
+    fn test_bag() {
+        let log = RefCell::new(Vec::new());
+        fill(&log, DestroyOrder::Lifo).destroy(());
+        fill(&log, DestroyOrder::Fifo).destroy(());
+        assert_eq!(
+            vec!["surface", "window", "window", "surface"],
+            log.into_inner()
+        );
+    }
+
+    #[test]
+    fn test_type_names() {
+        let log = RefCell::new(Vec::new());
+        let bag = fill(&log, DestroyOrder::Lifo);
+        let names: Vec<_> = bag.type_names().collect();
+        assert!(names[0].contains("Window"));
+        assert!(names[1].contains("Surface"));
+        assert!(format!("{:?}", bag).contains("Surface"));
+        bag.destroy(());
+    }
+
+    #[test]
+    fn test_dropping_non_empty_bag_is_violation() {
+        let log = RefCell::new(Vec::new());
+        let violations = scope(|s| {
+            let mut bag = DestroyBag::new();
+            bag.push(s.guard(Surface(&log)));
+        })
+        .unwrap_err();
+        assert_eq!(1, violations.len());
+        assert_eq!(Reason::Dropped, violations[0].reason());
+        assert!(violations[0].type_name().contains("Surface"));
+    }
+
+    #[test]
+    fn test_pushed_guard_keeps_fallback() {
+        let log = RefCell::new(Vec::new());
+        let mut bag = DestroyBag::new();
+        bag.push(MustDestroy::with_default_fallback(Window(&log)));
+        drop(bag);
+        assert_eq!(vec!["window"], log.into_inner());
+    }
+}
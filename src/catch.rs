@@ -0,0 +1,123 @@
+//! Catching violations raised by a block of code, for tests that expect them.
+use crate::Violation;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+
+thread_local! {
+    /// The violations caught by each `catch_violations` running on this thread, innermost
+    /// last.
+    static CATCHERS: RefCell<Vec<Vec<Violation>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `f`, catching the violations raised on this thread while it runs instead of
+/// handing them to their guards' policies.
+///
+/// Returns what `f` returned if there were no violations, otherwise every violation caught.
+/// A panic from `f` with a `Violation` payload, such as one raised on another thread by
+/// `PanicPolicy` and carried over by a join, is caught too. Any other panic carries on.
+///
+/// Violations from guards checked by a `scope` go to that scope instead.
+///
+// Dropping a guard is rejected by the `compile-time-check` feature.
+#[cfg_attr(feature = "compile-time-check", doc = "```ignore")]
+#[cfg_attr(not(feature = "compile-time-check"), doc = "```")]
+/// # use must_destroy::{catch_violations, MustDestroy, Reason};
+/// let violations = catch_violations(|| {
+///     drop(MustDestroy::new(1));
+///     drop(MustDestroy::new(2));
+/// })
+/// .unwrap_err();
+/// assert_eq!(2, violations.len());
+/// assert_eq!(Reason::Dropped, violations[0].reason());
+/// ```
+pub fn catch_violations<R, F: FnOnce() -> R>(f: F) -> Result<R, Vec<Violation>> {
+    CATCHERS.with(|catchers| catchers.borrow_mut().push(Vec::new()));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    let mut violations = CATCHERS.with(|catchers| catchers.borrow_mut().pop().unwrap_or_default());
+
+    match result {
+        Ok(result) if violations.is_empty() => Ok(result),
+        Ok(_) => Err(violations),
+        Err(payload) => match payload.downcast::<Violation>() {
+            Ok(violation) => {
+                violations.push(*violation);
+                Err(violations)
+            }
+            Err(payload) => panic::resume_unwind(payload),
+        },
+    }
+}
+
+/// Hands the violation to the innermost `catch_violations` running on this thread,
+/// returning `false` if there isn't one.
+pub(crate) fn catch(violation: &Violation) -> bool {
+    CATCHERS
+        .try_with(|catchers| match catchers.borrow_mut().last_mut() {
+            Some(caught) => {
+                caught.push(violation.clone());
+                true
+            }
+            None => false,
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{catch_violations, scope, MustDestroy, Reason, Violation};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::thread;
+
+    struct Lease;
+
+    #[test]
+    fn test_catch_violations() {
+        assert_eq!(Some(3), catch_violations(|| 1 + 2).ok());
+
+        let violations = catch_violations(|| {
+            drop(MustDestroy::new(Lease));
+            let inner = catch_violations(|| drop(MustDestroy::new(Lease)));
+            assert_eq!(1, inner.unwrap_err().len());
+            drop(MustDestroy::new(Lease));
+        })
+        .unwrap_err();
+        assert_eq!(2, violations.len());
+        assert!(violations[0].type_name().ends_with("Lease"));
+    }
+
+    #[test]
+    fn test_catches_violation_payloads() {
+        let violations = catch_violations(|| {
+            let guard = MustDestroy::new(Lease);
+            let payload = thread::spawn(move || drop(guard)).join().unwrap_err();
+            assert!(payload.downcast_ref::<Violation>().is_some());
+            std::panic::resume_unwind(payload);
+        })
+        .unwrap_err();
+        assert_eq!(1, violations.len());
+        assert_eq!(Reason::Dropped, violations[0].reason());
+    }
+
+    #[test]
+    fn test_other_panics_carry_on() {
+        let payload = catch_unwind(AssertUnwindSafe(|| {
+            let _ = catch_violations(|| panic!("original panic"));
+        }))
+        .unwrap_err();
+        assert_eq!(Some(&"original panic"), payload.downcast_ref::<&str>());
+    }
+
+    #[test]
+    fn test_catches_fallback_violations() {
+        let violations =
+            catch_violations(|| drop(MustDestroy::with_fallback(Lease, drop))).unwrap_err();
+        assert_eq!(1, violations.len());
+        assert_eq!(Reason::Fallback, violations[0].reason());
+    }
+
+    #[test]
+    fn test_scope_takes_its_own_violations() {
+        let caught = catch_violations(|| scope(|s| drop(s.guard(Lease))).unwrap_err().len());
+        assert_eq!(Some(1), caught.ok());
+    }
+}
@@ -0,0 +1,393 @@
+//! Collections of guards that refuse to silently drop their elements.
+//!
+//! Elements are handed back as armed [`MustDestroy`](crate::MustDestroy) guards when
+//! removed, and the collections themselves must be destroyed like one.
+use crate::held::Held;
+use crate::impls::destroy_each;
+use crate::origin::Origin;
+use crate::{DefaultPolicy, Destroy, DropPolicy, MustDestroy};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::iter::FromIterator;
+
+/// A `Vec` of items that must be destroyed.
+pub struct MustDestroyVec<T, P: DropPolicy = DefaultPolicy> {
+    items: Vec<Held<T, P>>,
+}
+
+impl<T> MustDestroyVec<T> {
+    /// Create a new, empty `MustDestroyVec`
+    pub fn new() -> Self {
+        MustDestroyVec::with_policy(DefaultPolicy)
+    }
+}
+
+impl<T> Default for MustDestroyVec<T> {
+    fn default() -> Self {
+        MustDestroyVec::new()
+    }
+}
+
+impl<T, P: DropPolicy> MustDestroyVec<T, P> {
+    /// Create a new, empty `MustDestroyVec`, whose items use `policy` if they're dropped
+    pub fn with_policy(_policy: P) -> Self {
+        MustDestroyVec { items: Vec::new() }
+    }
+
+    /// The number of items in the collection.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether the collection has no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Appends an item, recording the caller as where its guard was created.
+    #[track_caller]
+    pub fn push(&mut self, item: T) {
+        self.push_guard(MustDestroy::arm(item, Origin::here::<T>()));
+    }
+
+    /// Appends an item that is already guarded.
+    pub fn push_guard(&mut self, guard: MustDestroy<T, P>) {
+        self.items.push(Held::new(guard));
+    }
+
+    /// Removes the last item, handing it back still armed.
+    pub fn pop(&mut self) -> Option<MustDestroy<T, P>> {
+        self.items.pop().map(Held::into_guard)
+    }
+
+    /// Removes the item at `index`, shifting later items down, and hands it back still armed.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> MustDestroy<T, P> {
+        self.items.remove(index).into_guard()
+    }
+
+    /// Removes the item at `index`, replacing it with the last item, and hands it back still
+    /// armed.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> MustDestroy<T, P> {
+        self.items.swap_remove(index).into_guard()
+    }
+
+    /// The item at `index`, if there is one.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index).map(Held::get)
+    }
+
+    /// The item at `index`, if there is one.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.items.get_mut(index).map(Held::get_mut)
+    }
+
+    /// Iterates over the items in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items.iter().map(Held::get)
+    }
+
+    /// Iterates over the items in order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.items.iter_mut().map(Held::get_mut)
+    }
+
+    /// Destroys every item in order, with a clone of `args`, consuming the collection.
+    pub fn destroy_all<Args: Clone>(self, args: Args)
+    where
+        T: Destroy<Args>,
+    {
+        self.items.destroy(args);
+    }
+
+    /// Destroys every item in order, with the arguments `args` returns for it, leaving the
+    /// collection empty.
+    pub fn drain_destroy<Args, F: FnMut(&T) -> Args>(&mut self, mut args: F)
+    where
+        T: Destroy<Args>,
+    {
+        for item in self.items.drain(..) {
+            let args = args(item.get());
+            item.destroy(args);
+        }
+    }
+
+    /// Takes every item out as armed guards, consuming the collection.
+    pub fn into_guards(self) -> Vec<MustDestroy<T, P>> {
+        self.items.into_iter().map(Held::into_guard).collect()
+    }
+}
+
+impl<Args: Clone, T: Destroy<Args>, P: DropPolicy> Destroy<Args> for MustDestroyVec<T, P> {
+    /// destroys every item in order, with a clone of the arguments
+    fn destroy(self, args: Args) {
+        self.destroy_all(args);
+    }
+}
+
+impl<T, P: DropPolicy> Extend<MustDestroy<T, P>> for MustDestroyVec<T, P> {
+    fn extend<I: IntoIterator<Item = MustDestroy<T, P>>>(&mut self, guards: I) {
+        self.items.extend(guards.into_iter().map(Held::new));
+    }
+}
+
+impl<T, P: DropPolicy> FromIterator<MustDestroy<T, P>> for MustDestroyVec<T, P> {
+    fn from_iter<I: IntoIterator<Item = MustDestroy<T, P>>>(guards: I) -> Self {
+        MustDestroyVec {
+            items: guards.into_iter().map(Held::new).collect(),
+        }
+    }
+}
+
+/// A `HashMap` whose values must be destroyed.
+pub struct MustDestroyMap<K, V, P: DropPolicy = DefaultPolicy> {
+    items: HashMap<K, Held<V, P>>,
+}
+
+impl<K: Eq + Hash, V> MustDestroyMap<K, V> {
+    /// Create a new, empty `MustDestroyMap`
+    pub fn new() -> Self {
+        MustDestroyMap::with_policy(DefaultPolicy)
+    }
+}
+
+impl<K: Eq + Hash, V> Default for MustDestroyMap<K, V> {
+    fn default() -> Self {
+        MustDestroyMap::new()
+    }
+}
+
+impl<K: Eq + Hash, V, P: DropPolicy> MustDestroyMap<K, V, P> {
+    /// Create a new, empty `MustDestroyMap`, whose values use `policy` if they're dropped
+    pub fn with_policy(_policy: P) -> Self {
+        MustDestroyMap {
+            items: HashMap::new(),
+        }
+    }
+
+    /// The number of values in the map.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether the map has no values.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Inserts a value, recording the caller as where its guard was created.
+    ///
+    /// If the key already had a value, that value is handed back still armed.
+    #[track_caller]
+    pub fn insert(&mut self, key: K, value: V) -> Option<MustDestroy<V, P>> {
+        self.insert_guard(key, MustDestroy::arm(value, Origin::here::<V>()))
+    }
+
+    /// Inserts a value that is already guarded.
+    ///
+    /// If the key already had a value, that value is handed back still armed.
+    pub fn insert_guard(&mut self, key: K, guard: MustDestroy<V, P>) -> Option<MustDestroy<V, P>> {
+        self.items
+            .insert(key, Held::new(guard))
+            .map(Held::into_guard)
+    }
+
+    /// Removes the value for `key`, handing it back still armed.
+    pub fn remove<Q: ?Sized + Eq + Hash>(&mut self, key: &Q) -> Option<MustDestroy<V, P>>
+    where
+        K: Borrow<Q>,
+    {
+        self.items.remove(key).map(Held::into_guard)
+    }
+
+    /// Whether the map has a value for `key`.
+    pub fn contains_key<Q: ?Sized + Eq + Hash>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.items.contains_key(key)
+    }
+
+    /// The value for `key`, if there is one.
+    pub fn get<Q: ?Sized + Eq + Hash>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.items.get(key).map(Held::get)
+    }
+
+    /// The value for `key`, if there is one.
+    pub fn get_mut<Q: ?Sized + Eq + Hash>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        self.items.get_mut(key).map(Held::get_mut)
+    }
+
+    /// Iterates over the keys, in arbitrary order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.items.keys()
+    }
+
+    /// Iterates over the keys and values, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.items.iter().map(|(key, value)| (key, value.get()))
+    }
+
+    /// Iterates over the keys and values, in arbitrary order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&K, &mut V)> {
+        self.items
+            .iter_mut()
+            .map(|(key, value)| (key, value.get_mut()))
+    }
+
+    /// Destroys every value, in arbitrary order, with a clone of `args`, consuming the map.
+    pub fn destroy_all<Args: Clone>(self, args: Args)
+    where
+        V: Destroy<Args>,
+    {
+        destroy_each(self.items.into_values(), args);
+    }
+
+    /// Destroys every value, in arbitrary order, with the arguments `args` returns for it,
+    /// leaving the map empty.
+    pub fn drain_destroy<Args, F: FnMut(&K, &V) -> Args>(&mut self, mut args: F)
+    where
+        V: Destroy<Args>,
+    {
+        for (key, value) in self.items.drain() {
+            let args = args(&key, value.get());
+            value.destroy(args);
+        }
+    }
+
+    /// Takes every value out as armed guards, along with their keys, consuming the map.
+    pub fn into_guards(self) -> impl Iterator<Item = (K, MustDestroy<V, P>)> {
+        self.items
+            .into_iter()
+            .map(|(key, value)| (key, value.into_guard()))
+    }
+}
+
+impl<Args: Clone, K: Eq + Hash, V: Destroy<Args>, P: DropPolicy> Destroy<Args>
+    for MustDestroyMap<K, V, P>
+{
+    /// destroys every value, with a clone of the arguments
+    fn destroy(self, args: Args) {
+        self.destroy_all(args);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry::tests::ENABLING;
+    use crate::sync::lock;
+    use crate::{registry, scope, Destroy, MustDestroyMap, MustDestroyVec, Reason};
+    use std::any::type_name;
+    use std::cell::RefCell;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    type Log = RefCell<Vec<(u32, &'static str)>>;
+
+    /// Records its id and argument to the log when destroyed.
+    struct Buffer<'a>(u32, &'a Log);
+
+    impl<'a> Destroy<&'static str> for Buffer<'a> {
+        fn destroy(self, arg: &'static str) {
+            self.1.borrow_mut().push((self.0, arg));
+        }
+    }
+
+    #[test]
+    fn test_vec() {
+        let log = Log::default();
+        let mut buffers = MustDestroyVec::new();
+        for id in 1..=4 {
+            buffers.push(Buffer(id, &log));
+        }
+        assert_eq!(4, buffers.len());
+        assert_eq!(Some(2), buffers.get(1).map(|buffer| buffer.0));
+
+        buffers.pop().unwrap().destroy("pop");
+        buffers.remove(0).destroy("remove");
+        buffers.drain_destroy(|buffer| if buffer.0 == 2 { "two" } else { "other" });
+        assert!(buffers.is_empty());
+
+        buffers.push(Buffer(5, &log));
+        buffers.destroy_all("all");
+        assert_eq!(
+            vec![
+                (4, "pop"),
+                (1, "remove"),
+                (2, "two"),
+                (3, "other"),
+                (5, "all")
+            ],
+            log.into_inner()
+        );
+    }
+
+    #[test]
+    fn test_map() {
+        let log = Log::default();
+        let mut buffers = MustDestroyMap::new();
+        assert!(buffers.insert("a", Buffer(1, &log)).is_none());
+        buffers
+            .insert("a", Buffer(2, &log))
+            .unwrap()
+            .destroy("replaced");
+        buffers.insert("b", Buffer(3, &log));
+        assert_eq!(Some(2), buffers.get("a").map(|buffer| buffer.0));
+
+        buffers.remove("a").unwrap().destroy("remove");
+        assert!(!buffers.contains_key("a"));
+        buffers.drain_destroy(|key, _| key);
+        assert!(buffers.is_empty());
+
+        buffers.insert("c", Buffer(4, &log));
+        buffers.destroy("all");
+        assert_eq!(
+            vec![(1, "replaced"), (2, "remove"), (3, "b"), (4, "all")],
+            log.into_inner()
+        );
+    }
+
+    #[test]
+    fn test_dropping_non_empty_collection_is_violation() {
+        let log = Log::default();
+        let violations = scope(|s| {
+            let mut buffers = MustDestroyVec::new();
+            buffers.push_guard(s.guard(Buffer(1, &log)));
+            buffers.push_guard(s.guard(Buffer(2, &log)));
+            let mut map = MustDestroyMap::new();
+            map.insert_guard("a", s.guard(Buffer(3, &log)));
+        })
+        .unwrap_err();
+        assert_eq!(3, violations.len());
+        assert!(violations
+            .iter()
+            .all(|violation| violation.reason() == Reason::Dropped));
+        assert!(log.into_inner().is_empty());
+    }
+
+    /// Only created by the test below, so its guards can be told apart in the registry.
+    struct Registered;
+
+    #[test]
+    fn test_dropped_elements_are_unregistered_when_policy_panics() {
+        let _enabling = lock(&ENABLING);
+        registry::enable();
+        let mut registered = MustDestroyVec::new();
+        registered.push(Registered);
+        registered.push(Registered);
+        assert!(catch_unwind(AssertUnwindSafe(move || drop(registered))).is_err());
+        assert!(registry::outstanding()
+            .iter()
+            .all(|obligation| obligation.type_name() != type_name::<Registered>()));
+        registry::disable();
+    }
+}
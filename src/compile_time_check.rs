@@ -0,0 +1,144 @@
+//! With the `compile-time-check` feature, any code that would run the drop glue of a
+//! `MustDestroy` fails to build, rather than raising a violation at runtime.
+//!
+//! The check happens when the drop glue is instantiated for a concrete type, so it's only
+//! reported by `cargo build`, not by `cargo check`.
+//!
+//! Destroying a guard, or taking its value back with `into_inner`, is accepted:
+//!
+//! ```
+//! use must_destroy::{Destroy, MustDestroy};
+//!
+//! struct Buffer;
+//!
+//! impl Destroy<()> for Buffer {
+//!     fn destroy(self, _args: ()) {}
+//! }
+//!
+//! let destroyed = MustDestroy::new(Buffer);
+//! destroyed.destroy();
+//!
+//! let taken = MustDestroy::new(Buffer);
+//! let buffer = taken.into_inner();
+//! # drop(buffer);
+//! ```
+//!
+//! Dropping a guard explicitly is rejected:
+//!
+//! ```compile_fail,E0080
+//! use must_destroy::MustDestroy;
+//!
+//! let guard = MustDestroy::new(1u32);
+//! drop(guard);
+//! ```
+//!
+//! As is letting a guard go out of scope:
+//!
+//! ```compile_fail,E0080
+//! use must_destroy::MustDestroy;
+//!
+//! let _guard = MustDestroy::new(1u32);
+//! ```
+//!
+//! As is discarding a guard as soon as it's created:
+//!
+//! ```compile_fail,E0080
+//! use must_destroy::MustDestroy;
+//!
+//! let _ = MustDestroy::new(1u32);
+//! ```
+//!
+//! Unwinding drops every guard that is alive, so with `panic = "unwind"`, keeping a guard
+//! alive across anything that might panic is rejected too, even if the guard is destroyed
+//! afterwards. With `panic = "abort"` there is no unwinding, so this is accepted.
+//!
+//! ```compile_fail,E0080
+//! use must_destroy::{Destroy, MustDestroy};
+//!
+//! struct Buffer;
+//!
+//! impl Destroy<()> for Buffer {
+//!     fn destroy(self, _args: ()) {}
+//! }
+//!
+//! let guard = MustDestroy::new(Buffer);
+//! println!("about to destroy");
+//! guard.destroy();
+//! ```
+//!
+//! The check only covers `MustDestroy` itself, and not other types that can raise
+//! violations, such as `DestroyFuture`, which still do so at runtime. The containers provided
+//! by this crate hold guards without instantiating their drop glue, so they can still be used,
+//! and report violations by their items at runtime too.
+//!
+//! ```
+//! use must_destroy::{Destroy, MustDestroyVec};
+//!
+//! struct Buffer;
+//!
+//! impl Destroy<()> for Buffer {
+//!     fn destroy(self, _args: ()) {}
+//! }
+//!
+//! let mut buffers = MustDestroyVec::new();
+//! buffers.push(Buffer);
+//! buffers.push(Buffer);
+//! buffers.destroy_all(());
+//! ```
+//!
+//! `Option` and tuples of guards can still be destroyed in one call. Standard containers
+//! that allocate, such as `Vec`, `VecDeque` and `Box`, can't hold guards at all, as the
+//! standard library's code for them instantiates the drop glue of their items, even with
+//! `panic = "abort"`. Neither can arrays, which drop what's left of them if destroying an
+//! item panics. `MustDestroyVec` and the other containers of this crate take their place.
+//!
+//! ```
+//! use must_destroy::{Destroy, MustDestroy};
+//!
+//! struct Buffer;
+//!
+//! impl Destroy<()> for Buffer {
+//!     fn destroy(self, _args: ()) {}
+//! }
+//!
+//! Some(MustDestroy::new(Buffer)).destroy(());
+//! ```
+//!
+//! ```compile_fail,E0080
+//! use must_destroy::{Destroy, MustDestroy};
+//!
+//! struct Buffer;
+//!
+//! impl Destroy<()> for Buffer {
+//!     fn destroy(self, _args: ()) {}
+//! }
+//!
+//! vec![MustDestroy::new(Buffer)].destroy(());
+//! ```
+//!
+//! ```compile_fail,E0080
+//! use must_destroy::{Destroy, MustDestroy};
+//!
+//! struct Buffer;
+//!
+//! impl Destroy<()> for Buffer {
+//!     fn destroy(self, _args: ()) {}
+//! }
+//!
+//! Box::new(MustDestroy::new(Buffer)).destroy(());
+//! ```
+use std::marker::PhantomData;
+
+struct RejectDrop<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized> RejectDrop<T> {
+    const REJECT: () = panic!(
+        "`MustDestroy` can not be dropped, must call destroy (the `compile-time-check` feature is enabled)"
+    );
+}
+
+/// Fails to build once instantiated, which happens when a `MustDestroy<T>` can be dropped.
+pub(crate) fn reject_drop<T: ?Sized>() {
+    #[allow(clippy::let_unit_value)]
+    let () = RejectDrop::<T>::REJECT;
+}
@@ -0,0 +1,128 @@
+//! A queue of items that can only be destroyed once something in flight is done with them.
+use crate::held::Held;
+use crate::impls::destroy_each;
+use crate::{DefaultPolicy, Destroy, DropPolicy, MustDestroy};
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+/// Holds guards until an epoch, such as a frame or fence counter, has passed, then destroys
+/// them.
+///
+/// Like a [`MustDestroy`], the queue must be destroyed once it's no longer needed.
+pub struct DeferredDestroyQueue<T, Args, P: DropPolicy = DefaultPolicy> {
+    pending: BTreeMap<u64, Vec<Held<T, P>>>,
+    args: PhantomData<fn(Args)>,
+}
+
+impl<T: Destroy<Args>, Args: Clone> DeferredDestroyQueue<T, Args> {
+    /// Create a new, empty `DeferredDestroyQueue`
+    pub fn new() -> Self {
+        DeferredDestroyQueue::with_policy(DefaultPolicy)
+    }
+}
+
+impl<T: Destroy<Args>, Args: Clone> Default for DeferredDestroyQueue<T, Args> {
+    fn default() -> Self {
+        DeferredDestroyQueue::new()
+    }
+}
+
+impl<T: Destroy<Args>, Args: Clone, P: DropPolicy> DeferredDestroyQueue<T, Args, P> {
+    /// Create a new, empty `DeferredDestroyQueue` for guards using `policy`
+    pub fn with_policy(_policy: P) -> Self {
+        DeferredDestroyQueue {
+            pending: BTreeMap::new(),
+            args: PhantomData,
+        }
+    }
+
+    /// The number of guards waiting to be destroyed.
+    pub fn len(&self) -> usize {
+        self.pending.values().map(Vec::len).sum()
+    }
+
+    /// Whether no guards are waiting to be destroyed.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Defers destroying `guard` until `epoch` has passed.
+    pub fn defer(&mut self, epoch: u64, guard: MustDestroy<T, P>) {
+        self.pending
+            .entry(epoch)
+            .or_default()
+            .push(Held::new(guard));
+    }
+
+    /// Marks every epoch up to and including `epoch` as passed, destroying the guards
+    /// deferred until then with a clone of `args`. Returns how many were destroyed.
+    ///
+    /// Guards are destroyed in order of epoch, then in the order they were deferred.
+    pub fn advance(&mut self, epoch: u64, args: Args) -> usize {
+        let later = match epoch.checked_add(1) {
+            Some(next) => self.pending.split_off(&next),
+            None => BTreeMap::new(),
+        };
+        let passed = std::mem::replace(&mut self.pending, later);
+        let count = passed.values().map(Vec::len).sum();
+        destroy_each(passed.into_values().flatten(), args);
+        count
+    }
+}
+
+impl<T: Destroy<Args>, Args: Clone, P: DropPolicy> Destroy<Args>
+    for DeferredDestroyQueue<T, Args, P>
+{
+    /// destroys every guard still waiting, whatever its epoch, with a clone of the arguments
+    fn destroy(mut self, args: Args) {
+        self.advance(u64::MAX, args);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{scope, DeferredDestroyQueue, Destroy, MustDestroy, Reason};
+    use std::cell::RefCell;
+
+    /// Records its id and the frame it was destroyed on.
+    struct Buffer<'a>(u32, &'a RefCell<Vec<(u32, u64)>>);
+
+    impl<'a> Destroy<u64> for Buffer<'a> {
+        fn destroy(self, frame: u64) {
+            self.1.borrow_mut().push((self.0, frame));
+        }
+    }
+
+    #[test]
+    fn test_deferred() {
+        let log = RefCell::new(Vec::new());
+        let mut queue = DeferredDestroyQueue::new();
+        queue.defer(2, MustDestroy::new(Buffer(1, &log)));
+        queue.defer(1, MustDestroy::new(Buffer(2, &log)));
+        queue.defer(3, MustDestroy::new(Buffer(3, &log)));
+        queue.defer(1, MustDestroy::new(Buffer(4, &log)));
+
+        assert_eq!(0, queue.advance(0, 0));
+        assert_eq!(2, queue.advance(1, 1));
+        assert_eq!(2, queue.len());
+        assert_eq!(1, queue.advance(2, 2));
+
+        queue.destroy(10);
+        assert_eq!(vec![(2, 1), (4, 1), (1, 2), (3, 10)], log.into_inner());
+    }
+
+    #[test]
+    fn test_dropping_non_empty_queue_is_violation() {
+        let log = RefCell::new(Vec::new());
+        let violations = scope(|s| {
+            let mut queue = DeferredDestroyQueue::new();
+            queue.defer(1, s.guard(Buffer(1, &log)));
+            queue.defer(2, s.guard(Buffer(2, &log)));
+            queue.advance(1, 1);
+        })
+        .unwrap_err();
+        assert_eq!(1, violations.len());
+        assert_eq!(Reason::Dropped, violations[0].reason());
+        assert_eq!(vec![(1, 1)], log.into_inner());
+    }
+}
@@ -0,0 +1,131 @@
+//! `#[derive(Destroy)]`, composing the destructors of a type's fields.
+#[cfg(feature = "derive")]
+pub use must_destroy_derive::Destroy;
+
+#[cfg(test)]
+mod tests {
+    use crate::Destroy as _;
+    use crate::MustDestroy;
+    use must_destroy_derive::Destroy;
+    use std::cell::RefCell;
+
+    type Log = RefCell<Vec<(&'static str, u32)>>;
+
+    /// Records its name and argument to the log when destroyed.
+    struct Part(&'static str);
+
+    impl<'a> crate::Destroy<(&'a Log, u32)> for Part {
+        fn destroy(self, (log, arg): (&'a Log, u32)) {
+            log.borrow_mut().push((self.0, arg));
+        }
+    }
+
+    /// Records to the log when dropped.
+    struct Plain<'a>(&'a Log);
+
+    impl Drop for Plain<'_> {
+        fn drop(&mut self) {
+            self.0.borrow_mut().push(("plain", 0));
+        }
+    }
+
+    #[derive(Destroy)]
+    #[destroy(args = (&'a Log, u32))]
+    struct Resources<'a> {
+        first: MustDestroy<Part>,
+        #[destroy(with = (args.0, args.1 + 1))]
+        second: MustDestroy<Part>,
+        #[destroy]
+        third: Part,
+        plain: Plain<'a>,
+    }
+
+    #[test]
+    fn test_derive_struct() {
+        let log = Log::default();
+        Resources {
+            first: MustDestroy::new(Part("first")),
+            second: MustDestroy::new(Part("second")),
+            third: Part("third"),
+            plain: Plain(&log),
+        }
+        .destroy((&log, 1));
+        assert_eq!(
+            vec![("first", 1), ("second", 2), ("third", 1), ("plain", 0)],
+            log.into_inner()
+        );
+    }
+
+    #[derive(Destroy)]
+    #[destroy(args = (&'a Log, u32))]
+    struct Ordered<'a>(
+        MustDestroy<Part>,
+        #[destroy(order = 1)] MustDestroy<Part>,
+        #[destroy(order = 0)] MustDestroy<Part>,
+        #[destroy(skip)] MustDestroy<Plain<'a>>,
+    );
+
+    #[test]
+    fn test_derive_order_and_skip() {
+        let log = Log::default();
+        Ordered(
+            MustDestroy::new(Part("a")),
+            MustDestroy::new(Part("b")),
+            MustDestroy::new(Part("c")),
+            MustDestroy::new(Plain(&log)),
+        )
+        .destroy((&log, 0));
+        // The skipped field's item is dropped, without its guard raising a violation.
+        assert_eq!(
+            vec![("c", 0), ("b", 0), ("a", 0), ("plain", 0)],
+            log.into_inner()
+        );
+    }
+
+    #[derive(Destroy)]
+    #[destroy(args = (&'a Log, u32))]
+    struct Generic<'a, T: crate::Destroy<(&'a Log, u32)>> {
+        #[destroy]
+        inner: T,
+        plain: Plain<'a>,
+    }
+
+    #[test]
+    fn test_derive_generic() {
+        let log = Log::default();
+        Generic {
+            inner: MustDestroy::new(Part("inner")),
+            plain: Plain(&log),
+        }
+        .destroy((&log, 3));
+        assert_eq!(vec![("inner", 3), ("plain", 0)], log.into_inner());
+    }
+
+    #[derive(Destroy)]
+    #[destroy(args = (&'a Log, u32))]
+    enum Either<'a> {
+        Left(MustDestroy<Part>),
+        Right {
+            #[destroy(with = (args.0, 10))]
+            part: MustDestroy<Part>,
+            plain: Plain<'a>,
+        },
+        Neither,
+    }
+
+    #[test]
+    fn test_derive_enum() {
+        let log = Log::default();
+        Either::Left(MustDestroy::new(Part("left"))).destroy((&log, 1));
+        Either::Right {
+            part: MustDestroy::new(Part("right")),
+            plain: Plain(&log),
+        }
+        .destroy((&log, 1));
+        Either::Neither.destroy((&log, 1));
+        assert_eq!(
+            vec![("left", 1), ("right", 10), ("plain", 0)],
+            log.into_inner()
+        );
+    }
+}
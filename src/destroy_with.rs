@@ -0,0 +1,149 @@
+//! Calling destroy with plain arguments, rather than a tuple.
+use crate::Destroy;
+
+macro_rules! destroy_with {
+    ($($(#[$attr:meta])* $name:ident($($arg:ident: $ty:ident),+);)+) => {
+        $(
+            $(#[$attr])*
+            pub trait $name<$($ty),+> {
+                /// destroys the item being called upon, passing the arguments as a tuple
+                #[allow(clippy::too_many_arguments)]
+                fn destroy_with(self, $($arg: $ty),+);
+            }
+
+            impl<T: Destroy<($($ty,)+)>, $($ty),+> $name<$($ty),+> for T {
+                #[allow(clippy::too_many_arguments)]
+                fn destroy_with(self, $($arg: $ty),+) {
+                    self.destroy(($($arg,)+));
+                }
+            }
+        )+
+    };
+}
+
+destroy_with! {
+    /// Lets an item implementing `Destroy<(A, B)>` be destroyed with `destroy_with(a, b)`.
+    DestroyWith2(a: A, b: B);
+    /// Lets an item implementing `Destroy<(A, B, C)>` be destroyed with
+    /// `destroy_with(a, b, c)`.
+    DestroyWith3(a: A, b: B, c: C);
+    /// Lets an item implementing `Destroy<(A, B, C, D)>` be destroyed with
+    /// `destroy_with(a, b, c, d)`.
+    DestroyWith4(a: A, b: B, c: C, d: D);
+    /// Lets an item implementing `Destroy<(A, B, C, D, E)>` be destroyed with
+    /// `destroy_with(a, b, c, d, e)`.
+    DestroyWith5(a: A, b: B, c: C, d: D, e: E);
+    /// Lets an item implementing `Destroy<(A, B, C, D, E, F)>` be destroyed with
+    /// `destroy_with(a, b, c, d, e, f)`.
+    DestroyWith6(a: A, b: B, c: C, d: D, e: E, f: F);
+    /// Lets an item implementing `Destroy<(A, B, C, D, E, F, G)>` be destroyed with
+    /// `destroy_with(a, b, c, d, e, f, g)`.
+    DestroyWith7(a: A, b: B, c: C, d: D, e: E, f: F, g: G);
+    /// Lets an item implementing `Destroy<(A, B, C, D, E, F, G, H)>` be destroyed with
+    /// `destroy_with(a, b, c, d, e, f, g, h)`.
+    DestroyWith8(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H);
+}
+
+/// Destroys several items in one statement.
+///
+/// `destroy!(a, b)` destroys each item with `()`. `destroy!(a, b; x)` destroys each item
+/// with `x`, and `destroy!(a, b; x, y)` destroys each item with the tuple `(x, y)`. The
+/// arguments are evaluated once, and cloned for each item when there is more than one.
+///
+// Keeping two guards alive at once is rejected by the `compile-time-check` feature.
+#[cfg_attr(feature = "compile-time-check", doc = "```ignore")]
+#[cfg_attr(not(feature = "compile-time-check"), doc = "```")]
+/// use must_destroy::{destroy, Destroy, MustDestroy};
+///
+/// struct Buffer;
+///
+/// impl Destroy<(&'_ str, i32)> for Buffer {
+///     fn destroy(self, _args: (&str, i32)) {}
+/// }
+///
+/// let vertices = MustDestroy::new(Buffer);
+/// let indices = MustDestroy::new(Buffer);
+/// destroy!(vertices, indices; "device", 12);
+/// ```
+#[macro_export]
+macro_rules! destroy {
+    ($($item:expr),+ $(,)?) => {
+        $( $crate::Destroy::destroy($item, ()); )+
+    };
+    ($item:expr; $arg:expr $(,)?) => {
+        $crate::Destroy::destroy($item, $arg)
+    };
+    ($item:expr; $($arg:expr),+ $(,)?) => {
+        $crate::Destroy::destroy($item, ($($arg,)+))
+    };
+    ($($item:expr),+; $arg:expr $(,)?) => {{
+        let arg = $arg;
+        $( $crate::Destroy::destroy($item, ::core::clone::Clone::clone(&arg)); )+
+    }};
+    ($($item:expr),+; $($arg:expr),+ $(,)?) => {{
+        let args = ($($arg,)+);
+        $( $crate::Destroy::destroy($item, ::core::clone::Clone::clone(&args)); )+
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use std::cell::RefCell;
+
+    /// Records the arguments it is destroyed with.
+    struct Recorder<'a>(&'a RefCell<Vec<String>>);
+
+    impl<'a, A: std::fmt::Debug> Destroy<A> for Recorder<'a> {
+        fn destroy(self, args: A) {
+            self.0.borrow_mut().push(format!("{:?}", args));
+        }
+    }
+
+    struct Pair;
+
+    impl Destroy<(&'_ str, i32)> for Pair {
+        fn destroy(self, args: (&str, i32)) {
+            assert_eq!(("a", 1), args);
+        }
+    }
+
+    struct Eight;
+
+    impl Destroy<(u8, u8, u8, u8, u8, u8, u8, u8)> for Eight {
+        fn destroy(self, args: (u8, u8, u8, u8, u8, u8, u8, u8)) {
+            assert_eq!((1, 2, 3, 4, 5, 6, 7, 8), args);
+        }
+    }
+
+    #[test]
+    fn test_destroy_with() {
+        MustDestroy::new(Pair).destroy_with("a", 1);
+        MustDestroy::new(Eight).destroy_with(1, 2, 3, 4, 5, 6, 7, 8);
+        Pair.destroy_with("a", 1);
+    }
+
+    #[test]
+    fn test_destroy_macro() {
+        let log = RefCell::new(Vec::new());
+        let guard = || MustDestroy::new(Recorder(&log));
+        crate::destroy!(guard(), guard());
+        crate::destroy!(guard(); "one");
+        crate::destroy!(guard(); "a", 1);
+        crate::destroy!(guard(), guard(); String::from("shared"));
+        crate::destroy!(guard(), guard(); "b", 2,);
+        assert_eq!(
+            vec![
+                "()",
+                "()",
+                "\"one\"",
+                "(\"a\", 1)",
+                "\"shared\"",
+                "\"shared\"",
+                "(\"b\", 2)",
+                "(\"b\", 2)",
+            ],
+            log.into_inner()
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! An object safe form of `Destroy`, for destroying trait objects.
+use crate::{Destroy, DropPolicy, MustDestroy};
+use std::alloc::{dealloc, Layout};
+use std::ptr;
+
+mod sealed {
+    /// Lets this crate destroy an unsized guard's item, which it can't move out.
+    pub trait DestroyUnsized<Args> {
+        /// destroys the item being called upon, without freeing its memory.
+        ///
+        /// Unsafe because the item must not be used or dropped again afterwards.
+        unsafe fn destroy_unsized(&mut self, args: Args);
+    }
+}
+
+/// Object safe counterpart of `Destroy`, implemented for every item that can be destroyed.
+///
+/// `Box<dyn DynDestroy<Args>>` can itself be destroyed, so items of different types can be
+/// kept together, such as in a `Vec<MustDestroy<Box<dyn DynDestroy<Args>>>>`.
+pub trait DynDestroy<Args>: sealed::DestroyUnsized<Args> {
+    /// destroys the boxed item being called upon.
+    fn destroy_boxed(self: Box<Self>, args: Args);
+}
+
+impl<Args, T: Destroy<Args>> DynDestroy<Args> for T {
+    fn destroy_boxed(self: Box<Self>, args: Args) {
+        (*self).destroy(args);
+    }
+}
+
+impl<Args, T: Destroy<Args>> sealed::DestroyUnsized<Args> for T {
+    unsafe fn destroy_unsized(&mut self, args: Args) {
+        ptr::read(self).destroy(args);
+    }
+}
+
+impl<T: ?Sized, P: DropPolicy> MustDestroy<T, P> {
+    /// destroy and consume a boxed guard and its wrapped child, which can be unsized, such
+    /// as a `Box<MustDestroy<dyn DynDestroy<Args>>>`
+    pub fn destroy_boxed<Args>(self: Box<Self>, args: Args)
+    where
+        T: DynDestroy<Args>,
+    {
+        let raw = Box::into_raw(self);
+        // Safe because the guard is never used again: the origin is moved out, the wrapped
+        // item is destroyed where it lies, then the memory is freed without running `Drop`.
+        unsafe {
+            let layout = Layout::for_value(&*raw);
+            let origin = ptr::read(&(*raw).origin);
+            (*raw).wrapped.destroy_unsized(args);
+            if layout.size() != 0 {
+                dealloc(raw as *mut u8, layout);
+            }
+            drop(origin);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{scope, Destroy, DynDestroy, MustDestroy, Reason};
+    use std::cell::RefCell;
+
+    /// Records its name to the log when destroyed.
+    struct Shader<'a>(&'a RefCell<Vec<String>>);
+
+    impl<'a> Destroy<u32> for Shader<'a> {
+        fn destroy(self, frame: u32) {
+            self.0.borrow_mut().push(format!("shader {}", frame));
+        }
+    }
+
+    /// Records its name and size to the log when destroyed.
+    struct Buffer<'a>(&'a RefCell<Vec<String>>, [u8; 16]);
+
+    impl<'a> Destroy<u32> for Buffer<'a> {
+        fn destroy(self, frame: u32) {
+            self.0
+                .borrow_mut()
+                .push(format!("buffer {} {}", self.1.len(), frame));
+        }
+    }
+
+    #[test]
+    fn test_boxed_trait_objects() {
+        let log = RefCell::new(Vec::new());
+        let resources: Vec<MustDestroy<Box<dyn DynDestroy<u32> + '_>>> = vec![
+            MustDestroy::new(Box::new(Shader(&log))),
+            MustDestroy::new(Box::new(Buffer(&log, [0; 16]))),
+        ];
+        resources.destroy(3);
+        assert_eq!(vec!["shader 3", "buffer 16 3"], log.into_inner());
+    }
+
+    #[test]
+    fn test_unsized_guard() {
+        let log = RefCell::new(Vec::new());
+        let shader: Box<MustDestroy<dyn DynDestroy<u32> + '_>> =
+            Box::new(MustDestroy::new(Shader(&log)));
+        let buffer: Box<MustDestroy<dyn DynDestroy<u32> + '_>> =
+            Box::new(MustDestroy::new(Buffer(&log, [0; 16])));
+        shader.destroy_boxed(1);
+        buffer.destroy_boxed(2);
+        assert_eq!(vec!["shader 1", "buffer 16 2"], log.into_inner());
+    }
+
+    #[test]
+    fn test_dropping_unsized_guard_is_violation() {
+        let log = RefCell::new(Vec::new());
+        let violations = scope(|s| {
+            let buffer: Box<MustDestroy<dyn DynDestroy<u32> + '_>> =
+                Box::new(s.guard(Buffer(&log, [0; 16])));
+            drop(buffer);
+        })
+        .unwrap_err();
+        assert_eq!(1, violations.len());
+        assert_eq!(Reason::Dropped, violations[0].reason());
+        assert!(log.into_inner().is_empty());
+    }
+}
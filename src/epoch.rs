@@ -0,0 +1,218 @@
+//! Epoch based reclamation of guards shared with concurrent readers.
+use crate::held::Held;
+use crate::impls::destroy_each;
+use crate::sync::{self, lock};
+use crate::{DefaultPolicy, Destroy, DropPolicy, MustDestroy};
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Destroys retired guards once no reader that could still observe them is pinned.
+///
+/// Readers `pin` the collector for the length of their critical section. A guard retired
+/// after being unlinked from the shared structure is only destroyed by `collect` once every
+/// reader pinned before it was retired has unpinned.
+///
+/// Pinning never takes a lock, so readers don't hold each other up. Each `collect` moves
+/// the epoch on by one if no reader is still pinned at the one before it, and a guard is
+/// destroyed once the epoch has moved on twice since it was retired.
+///
+/// The collector must be destroyed, which destroys every guard still retired.
+pub struct Collector<T, Args, P: DropPolicy = DefaultPolicy> {
+    epoch: AtomicU64,
+    /// How many readers are pinned at even and at odd epochs. Readers are only ever pinned
+    /// at the current epoch or the one before it, so these tell them apart.
+    pinned: [AtomicUsize; 2],
+    /// Guards along with the epoch they were retired at, oldest first.
+    retired: Mutex<VecDeque<(u64, Held<T, P>)>>,
+    args: PhantomData<fn(Args)>,
+}
+
+impl<T: Destroy<Args>, Args: Clone> Collector<T, Args> {
+    /// Create a new `Collector`
+    pub fn new() -> Self {
+        Collector::with_policy(DefaultPolicy)
+    }
+}
+
+impl<T: Destroy<Args>, Args: Clone> Default for Collector<T, Args> {
+    fn default() -> Self {
+        Collector::new()
+    }
+}
+
+impl<T: Destroy<Args>, Args: Clone, P: DropPolicy> Collector<T, Args, P> {
+    /// Create a new `Collector` for guards using `policy`
+    pub fn with_policy(_policy: P) -> Self {
+        Collector {
+            epoch: AtomicU64::new(0),
+            pinned: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            retired: Mutex::new(VecDeque::new()),
+            args: PhantomData,
+        }
+    }
+
+    /// Pins the collector for a reader's critical section, which lasts until the returned
+    /// `EpochPin` is dropped.
+    ///
+    /// Pinning is sequentially consistent, so anything unlinked before a guard is retired
+    /// can't be reached by readers pinned after the epoch moves on.
+    pub fn pin(&self) -> EpochPin<'_> {
+        loop {
+            let epoch = self.epoch.load(Ordering::SeqCst);
+            let pinned = &self.pinned[(epoch % 2) as usize];
+            pinned.fetch_add(1, Ordering::SeqCst);
+            // If the epoch moved on in the meantime, the count may already have been
+            // checked, so try again at the new one.
+            if self.epoch.load(Ordering::SeqCst) == epoch {
+                return EpochPin { pinned };
+            }
+            pinned.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Retires a guard that has already been unlinked from whatever readers reach it
+    /// through, to be destroyed once no reader could still observe it.
+    pub fn retire(&self, guard: MustDestroy<T, P>) {
+        let mut retired = lock(&self.retired);
+        // Read while locked, as only `collect` moves the epoch on, so the retired guards
+        // stay in order.
+        let epoch = self.epoch.load(Ordering::SeqCst);
+        retired.push_back((epoch, Held::new(guard)));
+    }
+
+    /// The number of retired guards waiting to be destroyed.
+    pub fn pending(&self) -> usize {
+        lock(&self.retired).len()
+    }
+
+    /// Moves the epoch on if it can, then destroys every retired guard no reader could
+    /// still observe, with a clone of `args`. Returns how many were destroyed.
+    pub fn collect(&self, args: Args) -> usize {
+        let ready: Vec<_> = {
+            let mut retired = lock(&self.retired);
+            let mut epoch = self.epoch.load(Ordering::SeqCst);
+            if self.pinned[((epoch + 1) % 2) as usize].load(Ordering::SeqCst) == 0 {
+                // No reader is pinned at the epoch before, so every pinned reader is at the
+                // current one, which makes it the one before.
+                epoch += 1;
+                self.epoch.store(epoch, Ordering::SeqCst);
+            }
+            // Readers are pinned at `epoch` or the one before, so they were pinned after
+            // anything retired before then.
+            let count = retired.partition_point(|(retired, _)| retired + 2 <= epoch);
+            retired.drain(..count).map(|(_, guard)| guard).collect()
+        };
+        let count = ready.len();
+        destroy_each(ready, args);
+        count
+    }
+}
+
+impl<T: Destroy<Args>, Args: Clone, P: DropPolicy> Destroy<Args> for Collector<T, Args, P> {
+    /// destroys every guard still retired with a clone of the arguments, as no reader can
+    /// be pinned anymore
+    fn destroy(self, args: Args) {
+        let retired = sync::into_inner(self.retired);
+        destroy_each(retired.into_iter().map(|(_, guard)| guard), args);
+    }
+}
+
+/// Keeps a reader pinned to a `Collector`, holding off the destruction of anything retired
+/// while it's alive.
+pub struct EpochPin<'a> {
+    /// The count of readers pinned at the reader's epoch.
+    pinned: &'a AtomicUsize,
+}
+
+impl<'a> Drop for EpochPin<'a> {
+    fn drop(&mut self) {
+        self.pinned.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{scope, Collector, Destroy, MustDestroy, Reason};
+    use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+    use std::thread;
+
+    /// Counts how many times it was destroyed.
+    struct Node<'a>(u32, &'a AtomicUsize);
+
+    impl<'a> Destroy<()> for Node<'a> {
+        fn destroy(self, _args: ()) {
+            self.1.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_collector() {
+        let destroyed = AtomicUsize::new(0);
+        let collector = Collector::new();
+
+        let reader = collector.pin();
+        collector.retire(MustDestroy::new(Node(1, &destroyed)));
+        assert_eq!(0, collector.collect(()));
+        // The reader holds the epoch back until it unpins.
+        assert_eq!(0, collector.collect(()));
+        drop(reader);
+        assert_eq!(1, collector.collect(()));
+
+        collector.retire(MustDestroy::new(Node(2, &destroyed)));
+        let late_reader = collector.pin();
+        assert_eq!(0, collector.collect(()));
+        assert_eq!(0, collector.collect(()));
+        drop(late_reader);
+
+        assert_eq!(1, collector.pending());
+        collector.destroy(());
+        assert_eq!(2, destroyed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_concurrent_readers() {
+        let destroyed = AtomicUsize::new(0);
+        let collector = Collector::new();
+        let current = AtomicPtr::new(Box::into_raw(Box::new(Node(0, &destroyed))));
+
+        thread::scope(|threads| {
+            for _ in 0..4 {
+                threads.spawn(|| {
+                    for _ in 0..1000 {
+                        let _pin = collector.pin();
+                        // Safe because whatever is read can't be destroyed while pinned.
+                        let node = unsafe { &*current.load(Ordering::SeqCst) };
+                        assert!(node.0 < 100);
+                    }
+                });
+            }
+            for id in 1..100 {
+                let node = Box::into_raw(Box::new(Node(id, &destroyed)));
+                let old = current.swap(node, Ordering::SeqCst);
+                // Safe because `old` was unlinked above, and is only retired once.
+                collector.retire(MustDestroy::new(unsafe { Box::from_raw(old) }));
+                collector.collect(());
+            }
+        });
+
+        // Safe because every reader is done.
+        unsafe { Box::from_raw(current.load(Ordering::SeqCst)) }.destroy(());
+        collector.destroy(());
+        assert_eq!(100, destroyed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_dropping_collector_is_violation() {
+        let destroyed = AtomicUsize::new(0);
+        let violations = scope(|s| {
+            let collector = Collector::new();
+            let _pin = collector.pin();
+            collector.retire(s.guard(Node(1, &destroyed)));
+        })
+        .unwrap_err();
+        assert_eq!(1, violations.len());
+        assert_eq!(Reason::Dropped, violations[0].reason());
+    }
+}
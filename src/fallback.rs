@@ -0,0 +1,168 @@
+//! Per-guard fallback destructors, run instead of the policy when a guard is dropped.
+use crate::{DefaultPolicy, Destroy, DropPolicy, MustDestroy};
+use std::mem::{self, ManuallyDrop};
+use std::ptr;
+
+/// A guard's fallback destructor, with the type of the item it takes erased so it can be
+/// stored alongside an unsized item.
+#[derive(Clone, Copy)]
+pub(crate) struct Fallback {
+    /// The fallback, which is really a `fn(T)`.
+    fallback: fn(),
+    /// Moves the item out and calls the fallback with it.
+    run: unsafe fn(fn(), *mut ()),
+}
+
+/// Calls `fallback` with the item `item` points at, moving it out.
+unsafe fn run<T>(fallback: fn(), item: *mut ()) {
+    let fallback: fn(T) = mem::transmute(fallback);
+    fallback(ptr::read(item as *mut T));
+}
+
+/// Calls `fallback` with the item in the `Box<D>` that `item` points at, moving it out.
+unsafe fn run_boxed<T, D: ?Sized>(fallback: fn(), item: *mut ()) {
+    let fallback: fn(T) = mem::transmute(fallback);
+    let item = Box::into_raw(ptr::read(item as *mut Box<D>));
+    fallback(*Box::from_raw(item.cast::<T>()));
+}
+
+impl Fallback {
+    fn new<T>(fallback: fn(T)) -> Self {
+        Fallback {
+            // Safe because function pointers all have the same size, and `run::<T>` turns
+            // it back into a `fn(T)` before calling it.
+            fallback: unsafe { mem::transmute::<fn(T), fn()>(fallback) },
+            run: run::<T>,
+        }
+    }
+
+    /// The same fallback, for when its item has been moved into a `Box<D>`, such as a
+    /// `Box<dyn DynDestroy<Args>>`.
+    ///
+    /// Unsafe because the box must hold the item the fallback was made for.
+    pub(crate) unsafe fn boxed<T, D: ?Sized>(self) -> Self {
+        Fallback {
+            fallback: self.fallback,
+            run: run_boxed::<T, D>,
+        }
+    }
+
+    /// Runs the fallback on `item`, moving it out.
+    ///
+    /// Unsafe because `item` must be the item the fallback was made for, and must not be
+    /// touched again afterwards.
+    pub(crate) unsafe fn run<T: ?Sized>(self, item: &mut ManuallyDrop<T>) {
+        (self.run)(self.fallback, &mut **item as *mut T as *mut ());
+    }
+}
+
+/// Destroys `item` with the default arguments.
+fn destroy_with_default<T: Destroy<Args>, Args: Default>(item: T) {
+    item.destroy(Args::default());
+}
+
+impl<T> MustDestroy<T> {
+    /// Create a new `MustDestroy` for the given item, calling `fallback` with it if it's
+    /// dropped, instead of panicking
+    ///
+    /// The drop is still counted as a violation with `Reason::Fallback`, and reported as a
+    /// warning, or to the guard's scope or `catch_violations`. With the `compile-time-check`
+    /// feature, dropping the guard still fails to build.
+    #[track_caller]
+    pub fn with_fallback(item: T, fallback: fn(T)) -> Self {
+        MustDestroy::with_policy_and_fallback(item, DefaultPolicy, fallback)
+    }
+
+    /// Create a new `MustDestroy` for the given item, destroying it with the default
+    /// arguments if it's dropped, instead of panicking
+    ///
+    /// This is `with_fallback` with a fallback calling `destroy(Args::default())`.
+    #[track_caller]
+    pub fn with_default_fallback<Args: Default>(item: T) -> Self
+    where
+        T: Destroy<Args>,
+    {
+        MustDestroy::with_fallback(item, destroy_with_default::<T, Args>)
+    }
+}
+
+impl<T, P: DropPolicy> MustDestroy<T, P> {
+    /// Create a new `MustDestroy` for the given item, calling `fallback` with it if it's
+    /// dropped, instead of using `policy`
+    ///
+    /// The guard is typed by `policy`, so it can be held wherever guards using it are, such
+    /// as a `DestroyPool` with that policy.
+    #[track_caller]
+    pub fn with_policy_and_fallback(item: T, policy: P, fallback: fn(T)) -> Self {
+        let mut guard = MustDestroy::with_policy(item, policy);
+        guard.set_fallback(fallback);
+        guard
+    }
+
+    /// Makes `fallback` the guard's fallback destructor, replacing any it had.
+    pub(crate) fn set_fallback(&mut self, fallback: fn(T)) {
+        self.fallback = Some(Fallback::new(fallback));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{catch_violations, scope, Destroy, LogPolicy, MustDestroy, Reason};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Adds to its count when closed, by 10 when flushing and by 1 otherwise.
+    struct File(&'static AtomicUsize);
+
+    impl Destroy<bool> for File {
+        fn destroy(self, flush: bool) {
+            self.0
+                .fetch_add(if flush { 10 } else { 1 }, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_fallback() {
+        static CLOSED: AtomicUsize = AtomicUsize::new(0);
+        let violations = catch_violations(|| {
+            let file = MustDestroy::with_fallback(File(&CLOSED), |file| file.destroy(false));
+            drop(file);
+        })
+        .unwrap_err();
+        assert_eq!(1, CLOSED.load(Ordering::SeqCst));
+        assert_eq!(1, violations.len());
+        assert_eq!(Reason::Fallback, violations[0].reason());
+    }
+
+    #[test]
+    fn test_default_fallback() {
+        static CLOSED: AtomicUsize = AtomicUsize::new(0);
+        drop(MustDestroy::with_default_fallback(File(&CLOSED)));
+        assert_eq!(1, CLOSED.load(Ordering::SeqCst));
+
+        // Destroying the guard as usual doesn't run the fallback.
+        MustDestroy::with_default_fallback(File(&CLOSED)).destroy(true);
+        assert_eq!(11, CLOSED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_fallback_with_policy() {
+        static CLOSED: AtomicUsize = AtomicUsize::new(0);
+        let file = MustDestroy::with_policy_and_fallback(File(&CLOSED), LogPolicy, |file| {
+            file.destroy(false)
+        });
+        drop(file);
+        assert_eq!(1, CLOSED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_fallback_reported_to_scope() {
+        static CLOSED: AtomicUsize = AtomicUsize::new(0);
+        let violations = scope(|s| {
+            drop(s.guard_with_fallback(File(&CLOSED), |file| file.destroy(false)));
+        })
+        .unwrap_err();
+        assert_eq!(1, violations.len());
+        assert_eq!(Reason::Fallback, violations[0].reason());
+        assert_eq!(1, CLOSED.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,100 @@
+//! A process wide hook observing every violation.
+use crate::Violation;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// A handler observing violations, as registered by `set_violation_handler`.
+pub type ViolationHandler = Box<dyn Fn(&Violation) + Send + Sync>;
+
+/// The registered handler, kept in an `Arc` so it can be cloned out of the lock before
+/// being called.
+type SharedHandler = Arc<dyn Fn(&Violation) + Send + Sync>;
+
+static HANDLER: RwLock<Option<SharedHandler>> = RwLock::new(None);
+
+/// Registers a handler called with every violation, replacing any previous one.
+///
+/// The handler is called as soon as a violation is counted, before the guard's policy, or
+/// its scope, acts upon it. Like a panic hook, it's meant for reporting, such as sending
+/// violations to a logging or metrics system.
+pub fn set_violation_handler(handler: ViolationHandler) {
+    *HANDLER.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::from(handler));
+}
+
+/// Unregisters the violation handler, returning it if there was one.
+pub fn take_violation_handler() -> Option<ViolationHandler> {
+    HANDLER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+        .map(|handler| {
+            Box::new(move |violation: &Violation| handler(violation)) as ViolationHandler
+        })
+}
+
+/// Calls the violation handler, if there is one.
+pub(crate) fn notify(violation: &Violation) {
+    // Cloned out so the lock isn't held while the handler runs, as it may drop guards or
+    // replace itself.
+    let handler = HANDLER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    if let Some(handler) = handler {
+        handler(violation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{set_violation_handler, take_violation_handler, LogPolicy, MustDestroy, Reason};
+    use std::sync::Mutex;
+
+    /// Held by tests registering a handler, as there's only one.
+    static REGISTERING: Mutex<()> = Mutex::new(());
+
+    /// Only dropped by this test, so other tests' violations can be told apart.
+    struct Observed;
+
+    #[test]
+    fn test_violation_handler() {
+        static SEEN: Mutex<Vec<(&str, Reason)>> = Mutex::new(Vec::new());
+        let _registering = REGISTERING.lock().unwrap();
+        set_violation_handler(Box::new(|violation| {
+            if violation.type_name().ends_with("Observed") {
+                SEEN.lock()
+                    .unwrap()
+                    .push((violation.type_name(), violation.reason()));
+            }
+        }));
+        drop(MustDestroy::with_policy(Observed, LogPolicy));
+        assert!(take_violation_handler().is_some());
+        drop(MustDestroy::with_policy(Observed, LogPolicy));
+
+        let seen = SEEN.lock().unwrap();
+        assert_eq!(1, seen.len());
+        assert_eq!(Reason::Dropped, seen[0].1);
+    }
+
+    /// Only dropped by this test, by the handler itself.
+    struct Reentrant;
+
+    #[test]
+    fn test_handler_can_drop_guards_and_unregister_itself() {
+        static SEEN: Mutex<usize> = Mutex::new(0);
+        let _registering = REGISTERING.lock().unwrap();
+        set_violation_handler(Box::new(|violation| {
+            if violation.type_name().ends_with("Reentrant") {
+                let mut seen = SEEN.lock().unwrap();
+                *seen += 1;
+                if *seen == 1 {
+                    drop(seen);
+                    drop(MustDestroy::with_policy(Reentrant, LogPolicy));
+                    take_violation_handler();
+                }
+            }
+        }));
+        drop(MustDestroy::with_policy(Reentrant, LogPolicy));
+        assert_eq!(2, *SEEN.lock().unwrap());
+        assert!(take_violation_handler().is_none());
+    }
+}
@@ -0,0 +1,48 @@
+//! Guards held inside the containers provided by this crate.
+use crate::{Destroy, DropPolicy, MustDestroy};
+use std::mem::ManuallyDrop;
+
+/// A guard held inside one of this crate's containers.
+///
+/// Dropping it is reported like dropping the guard, but without instantiating the guard's
+/// drop glue. This keeps the containers usable with the `compile-time-check` feature, where
+/// they report at runtime instead.
+pub(crate) struct Held<T, P: DropPolicy>(ManuallyDrop<MustDestroy<T, P>>);
+
+impl<T, P: DropPolicy> Held<T, P> {
+    pub(crate) fn new(guard: MustDestroy<T, P>) -> Self {
+        Held(ManuallyDrop::new(guard))
+    }
+
+    /// Takes the guard back out, still armed.
+    pub(crate) fn into_guard(self) -> MustDestroy<T, P> {
+        let mut this = ManuallyDrop::new(self);
+        // Safe because `this` is never dropped or used again.
+        unsafe { ManuallyDrop::take(&mut this.0) }
+    }
+
+    pub(crate) fn guard(&self) -> &MustDestroy<T, P> {
+        &self.0
+    }
+
+    pub(crate) fn get(&self) -> &T {
+        &self.0
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<Args, T: Destroy<Args>, P: DropPolicy> Destroy<Args> for Held<T, P> {
+    fn destroy(self, args: Args) {
+        self.into_guard().destroy(args);
+    }
+}
+
+impl<T, P: DropPolicy> Drop for Held<T, P> {
+    fn drop(&mut self) {
+        // Safe because `self.0` is never touched again after this.
+        unsafe { ManuallyDrop::take(&mut self.0) }.abandon();
+    }
+}
@@ -0,0 +1,160 @@
+//! `Destroy` for standard containers, smart pointers and tuples of destroyable items.
+//!
+//! Containers of a single item type destroy each item in order, broadcasting a clone of
+//! `Args` to every item. Tuples take a tuple of arguments, one for each element.
+use crate::{Destroy, DynDestroy};
+use std::collections::VecDeque;
+
+/// Destroys each item in order with a clone of `args`, handing the last item `args` itself.
+pub(crate) fn destroy_each<T: Destroy<Args>, Args: Clone>(
+    items: impl IntoIterator<Item = T>,
+    args: Args,
+) {
+    let mut items = items.into_iter().peekable();
+    while let Some(item) = items.next() {
+        if items.peek().is_none() {
+            item.destroy(args);
+            return;
+        }
+        item.destroy(args.clone());
+    }
+}
+
+impl<Args: Clone, T: Destroy<Args>> Destroy<Args> for Vec<T> {
+    /// destroys each item in order, with a clone of the arguments
+    fn destroy(self, args: Args) {
+        destroy_each(self, args);
+    }
+}
+
+impl<Args: Clone, T: Destroy<Args>> Destroy<Args> for VecDeque<T> {
+    /// destroys each item from front to back, with a clone of the arguments
+    fn destroy(self, args: Args) {
+        destroy_each(self, args);
+    }
+}
+
+impl<Args: Clone, T: Destroy<Args>, const N: usize> Destroy<Args> for [T; N] {
+    /// destroys each item in order, with a clone of the arguments
+    fn destroy(self, args: Args) {
+        destroy_each(IntoIterator::into_iter(self), args);
+    }
+}
+
+impl<Args, T: Destroy<Args>> Destroy<Args> for Option<T> {
+    /// destroys the item if there is one
+    fn destroy(self, args: Args) {
+        if let Some(item) = self {
+            item.destroy(args);
+        }
+    }
+}
+
+impl<Args, T: ?Sized + DynDestroy<Args>> Destroy<Args> for Box<T> {
+    /// destroys the boxed item, which can be a trait object
+    fn destroy(self, args: Args) {
+        self.destroy_boxed(args);
+    }
+}
+
+macro_rules! tuple_destroy {
+    ($(($($item:ident: $args:ident),+))+) => {
+        $(
+            #[allow(non_snake_case)]
+            impl<$($item: Destroy<$args>, $args),+> Destroy<($($args,)+)> for ($($item,)+) {
+                /// destroys each element in order, with the matching element of the arguments
+                fn destroy(self, args: ($($args,)+)) {
+                    let ($($item,)+) = self;
+                    let ($($args,)+) = args;
+                    $( $item.destroy($args); )+
+                }
+            }
+        )+
+    };
+}
+
+tuple_destroy! {
+    (A: AArgs)
+    (A: AArgs, B: BArgs)
+    (A: AArgs, B: BArgs, C: CArgs)
+    (A: AArgs, B: BArgs, C: CArgs, D: DArgs)
+    (A: AArgs, B: BArgs, C: CArgs, D: DArgs, E: EArgs)
+    (A: AArgs, B: BArgs, C: CArgs, D: DArgs, E: EArgs, F: FArgs)
+    (A: AArgs, B: BArgs, C: CArgs, D: DArgs, E: EArgs, F: FArgs, G: GArgs)
+    (A: AArgs, B: BArgs, C: CArgs, D: DArgs, E: EArgs, F: FArgs, G: GArgs, H: HArgs)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Destroy, MustDestroy};
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    type Log = RefCell<Vec<(u32, &'static str)>>;
+
+    /// Records its id and argument to the log when destroyed.
+    struct Buffer<'a>(u32, &'a Log);
+
+    impl<'a> Destroy<&'static str> for Buffer<'a> {
+        fn destroy(self, arg: &'static str) {
+            self.1.borrow_mut().push((self.0, arg));
+        }
+    }
+
+    #[test]
+    fn test_containers_broadcast_args() {
+        let log = Log::default();
+        let guard = |id| MustDestroy::new(Buffer(id, &log));
+
+        vec![guard(1), guard(2)].destroy("vec");
+        VecDeque::from(vec![guard(3), guard(4)]).destroy("deque");
+        [guard(5), guard(6)].destroy("array");
+        Vec::<MustDestroy<Buffer<'_>>>::new().destroy("empty");
+        assert_eq!(
+            vec![
+                (1, "vec"),
+                (2, "vec"),
+                (3, "deque"),
+                (4, "deque"),
+                (5, "array"),
+                (6, "array"),
+            ],
+            log.into_inner()
+        );
+    }
+
+    #[test]
+    fn test_option_and_box() {
+        let log = Log::default();
+        Some(MustDestroy::new(Buffer(1, &log))).destroy("some");
+        None::<MustDestroy<Buffer<'_>>>.destroy("none");
+        Box::new(MustDestroy::new(Buffer(2, &log))).destroy("box");
+        vec![Some(MustDestroy::new(Buffer(3, &log))), None].destroy("nested");
+        assert_eq!(
+            vec![(1, "some"), (2, "box"), (3, "nested")],
+            log.into_inner()
+        );
+    }
+
+    #[test]
+    fn test_tuples_take_args_per_element() {
+        struct Counter<'a>(&'a RefCell<u32>);
+
+        impl<'a> Destroy<u32> for Counter<'a> {
+            fn destroy(self, amount: u32) {
+                *self.0.borrow_mut() += amount;
+            }
+        }
+
+        let log = Log::default();
+        let count = RefCell::new(0);
+        (
+            MustDestroy::new(Buffer(1, &log)),
+            MustDestroy::new(Counter(&count)),
+            vec![MustDestroy::new(Buffer(2, &log))],
+        )
+            .destroy(("first", 5, "third"));
+        assert_eq!(vec![(1, "first"), (2, "third")], log.into_inner());
+        assert_eq!(5, count.into_inner());
+    }
+}
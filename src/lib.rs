@@ -8,13 +8,93 @@
 //!
 //! However, calling destroy upon the guard, will call destroy on wrapped child, and will
 //! be consumed safely.
-use std::mem::forget;
+//!
+//! What happens when a guard is dropped is decided by its `DropPolicy`, which defaults
+//! to `DefaultPolicy`. That panics, unless the `MUST_DESTROY_ON_VIOLATION` environment
+//! variable says otherwise.
+//!
+//! Guards can be held in containers such as `MustDestroyVec`, `DestroyArena`, `DestroyPool`
+//! and `DestroyBag`, and ones that were never destroyed can be found through the `registry`,
+//! `scope`, `catch_violations` or the `compile-time-check` feature.
+use fallback::Fallback;
+use origin::Origin;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
 use std::ops::{Deref, DerefMut, Drop};
+use std::ptr;
+
+// Lets code generated by `#[derive(Destroy)]` refer to `::must_destroy` within this crate.
+extern crate self as must_destroy;
+
+mod arena;
+mod async_destroy;
+mod bag;
+mod catch;
+mod collections;
+#[cfg(all(feature = "compile-time-check", not(test)))]
+mod compile_time_check;
+mod deferred;
+mod derive;
+mod destroy_with;
+mod dyn_destroy;
+mod epoch;
+mod fallback;
+mod handler;
+mod held;
+mod impls;
+mod origin;
+mod pinned;
+mod policy;
+mod pool;
+pub mod registry;
+mod scope;
+mod shared;
+mod slot;
+mod sync;
+mod try_destroy;
+mod violation;
+
+pub use arena::{ArenaHandle, DestroyArena};
+pub use async_destroy::{AsyncDestroy, DestroyFuture};
+pub use bag::{DestroyBag, DestroyOrder};
+pub use catch::catch_violations;
+pub use collections::{MustDestroyMap, MustDestroyVec};
+pub use deferred::DeferredDestroyQueue;
+#[cfg(feature = "derive")]
+pub use derive::Destroy;
+pub use destroy_with::{
+    DestroyWith2, DestroyWith3, DestroyWith4, DestroyWith5, DestroyWith6, DestroyWith7,
+    DestroyWith8,
+};
+pub use dyn_destroy::DynDestroy;
+pub use epoch::{Collector, EpochPin};
+pub use handler::{set_violation_handler, take_violation_handler, ViolationHandler};
+pub use pinned::{DestroyInPlace, PinnedMustDestroy};
+pub use policy::{
+    AbortPolicy, DebugPanicPolicy, DefaultPolicy, Disposal, DropPolicy, LeakPolicy, LogPolicy,
+    PanicPolicy,
+};
+pub use pool::{Checkout, DestroyPool};
+pub use scope::{scope, Scope};
+pub use shared::{SharedMustDestroy, WeakMustDestroy};
+pub use slot::{DestroySlot, SlotError};
+pub use try_destroy::{DestroyError, TryDestroy};
+pub use violation::{violation_count, Reason, Violation};
+
+/// Brings `Destroy`, `MustDestroy` and the `DestroyWith` traits into scope, so
+/// `destroy_with(a, b)` can be called without a tuple.
+pub mod prelude {
+    pub use crate::{
+        Destroy, DestroyWith2, DestroyWith3, DestroyWith4, DestroyWith5, DestroyWith6,
+        DestroyWith7, DestroyWith8, MustDestroy,
+    };
+}
 
 /// Trait applied to items that can be destroyed.
 ///
 /// `Args` represents the type to act as an arguments to the destructor. For multiple
-/// arguments you can use a `tuple`
+/// arguments you can use a `tuple`, which the `DestroyWith` traits let callers pass as
+/// plain arguments instead
 pub trait Destroy<Args> {
     /// destroys the item being called upon.
     fn destroy(self, args: Args);
@@ -22,51 +102,132 @@ pub trait Destroy<Args> {
 
 /// The value contained is an item that can't be dropped and must be
 /// destroyed via calling it's `Destroy::destroy` method.
-pub struct MustDestroy<T> {
-    wrapped: T
+///
+/// `P` is the `DropPolicy` deciding what happens if it is dropped anyway. The containers of
+/// this crate, such as `MustDestroyVec` or `DestroyArena`, must be destroyed in the same
+/// way: dropping one that still holds guards raises a violation for each of them.
+///
+/// The item can be unsized, such as a `Box<MustDestroy<dyn DynDestroy<Args>>>`, which is
+/// destroyed through `destroy_boxed`.
+pub struct MustDestroy<T: ?Sized, P: DropPolicy = DefaultPolicy> {
+    origin: Origin,
+    policy: PhantomData<fn() -> P>,
+    // Run instead of the policy if the guard is dropped.
+    fallback: Option<Fallback>,
+    // `ManuallyDrop` lets us move the value out in `into_inner` without leaving
+    // anything behind for `Drop` to touch. Being the last field lets it be unsized.
+    wrapped: ManuallyDrop<T>,
 }
 
 impl<T> MustDestroy<T> {
     /// Create a new `MustDestroy` for the given item
+    #[track_caller]
     pub fn new(item: T) -> Self {
+        MustDestroy::with_policy(item, DefaultPolicy)
+    }
+}
+
+impl<T, P: DropPolicy> MustDestroy<T, P> {
+    /// Create a new `MustDestroy` for the given item, using `policy` if it's dropped
+    #[track_caller]
+    pub fn with_policy(item: T, _policy: P) -> Self {
+        MustDestroy::arm(item, Origin::here::<T>())
+    }
+
+    /// Wraps `item` in a new guard, created at `origin`.
+    pub(crate) fn arm(item: T, origin: Origin) -> Self {
+        MustDestroy::rearm(item, origin, None)
+    }
+
+    /// Wraps `item` back up in a guard taken apart by `disarm`.
+    pub(crate) fn rearm(item: T, origin: Origin, fallback: Option<Fallback>) -> Self {
         MustDestroy {
-            wrapped: item,
+            origin,
+            wrapped: ManuallyDrop::new(item),
+            policy: PhantomData,
+            fallback,
         }
     }
 
     /// Removes the contained item from the MustDestroy guard
-    pub fn into_inner(mut self) -> T {
-        // Safe because we never actually use the zeroed value, we just need to get the original
-        // value out of the struct, without dropping it.
-        let wrapped = std::mem::replace(&mut self.wrapped, unsafe { std::mem::zeroed() });
-        // as self is consumed by the function, and without the wrapped value there is
-        // nothing else to do, we can just forget ourselves.
-        forget(self);
-        wrapped
+    pub fn into_inner(self) -> T {
+        self.disarm().0
+    }
+
+    /// Removes the contained item from the guard, along with where the guard was created
+    /// and its fallback, so it can be put back together by `rearm`.
+    fn disarm(self) -> (T, Origin, Option<Fallback>) {
+        // Our own `Drop` must not run, as the value is being handed back.
+        let mut this = ManuallyDrop::new(self);
+        // Safe because `this` is never dropped or used again, so each field is
+        // moved out exactly once.
+        unsafe {
+            let origin = ptr::read(&this.origin);
+            (ManuallyDrop::take(&mut this.wrapped), origin, this.fallback)
+        }
+    }
+
+    /// Raises a violation for the guard, as though it was dropped, but without running its
+    /// `Drop`.
+    pub(crate) fn abandon(self) {
+        let mut this = ManuallyDrop::new(self);
+        // Safe because `this` is never dropped or used again, so the origin is dropped
+        // exactly once, by the local. Moving it out first means it's still dropped if the
+        // policy panics.
+        unsafe {
+            let _origin = ptr::read(&this.origin);
+            this.violate();
+        }
     }
 }
 
-impl<Args, T: Destroy<Args>> Destroy<Args> for MustDestroy<T> {
+impl<T: ?Sized, P: DropPolicy> MustDestroy<T, P> {
+    /// Raises a violation for the guard being dropped, disposing of the wrapped value as
+    /// the policy says, or handing it to the fallback if there is one.
+    ///
+    /// Unsafe because the wrapped value must not be touched again afterwards.
+    unsafe fn violate(&mut self) {
+        if let Some(fallback) = self.fallback {
+            fallback.run(&mut self.wrapped);
+            self.origin
+                .warn(Violation::new(&self.origin, Reason::Fallback));
+            return;
+        }
+        // Dispose of the wrapped value before the policy runs, so it isn't leaked if the
+        // policy panics.
+        if P::disposal() == Disposal::Drop {
+            ManuallyDrop::drop(&mut self.wrapped);
+        }
+        self.origin.report::<P>(Violation::dropped(&self.origin));
+    }
+}
+
+impl<Args, T: Destroy<Args>, P: DropPolicy> Destroy<Args> for MustDestroy<T, P> {
     /// destroy and consume self and wrapped child
     fn destroy(self, args: Args) {
         self.into_inner().destroy(args);
     }
 }
 
-impl<T: Destroy<()>> MustDestroy<T> {
+impl<T: Destroy<()>, P: DropPolicy> MustDestroy<T, P> {
     /// destroy and consume self and wrapped child
     pub fn destroy(self) {
         Destroy::destroy(self, ())
     }
 }
 
-impl<T> Drop for MustDestroy<T> {
+impl<T: ?Sized, P: DropPolicy> Drop for MustDestroy<T, P> {
     fn drop(&mut self) {
-        panic!("Can not drop, must call destroy.");
+        // Our own unit tests need to see what happens at runtime.
+        #[cfg(all(feature = "compile-time-check", not(test)))]
+        compile_time_check::reject_drop::<T>();
+
+        // Safe because `wrapped` is never touched again after this.
+        unsafe { self.violate() };
     }
 }
 
-impl<T> Deref for MustDestroy<T> {
+impl<T: ?Sized, P: DropPolicy> Deref for MustDestroy<T, P> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -74,7 +235,7 @@ impl<T> Deref for MustDestroy<T> {
     }
 }
 
-impl<T> DerefMut for MustDestroy<T> {
+impl<T: ?Sized, P: DropPolicy> DerefMut for MustDestroy<T, P> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.wrapped
     }
@@ -82,7 +243,116 @@ impl<T> DerefMut for MustDestroy<T> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Destroy, MustDestroy};
+    use crate::{Destroy, MustDestroy, Reason};
+    use std::cell::Cell;
+    use std::num::NonZeroU32;
+    use std::ptr::NonNull;
+    use std::rc::Rc;
+
+    /// Destroys by checking the value against the expected argument.
+    struct Expect<T>(T);
+
+    impl<T: PartialEq + std::fmt::Debug> Destroy<T> for Expect<T> {
+        fn destroy(self, expected: T) {
+            assert_eq!(expected, self.0);
+        }
+    }
+
+    /// Counts how many times it has been dropped.
+    struct DropCounter(Rc<Cell<usize>>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Handle {
+        Open(Box<u32>),
+        Closed,
+    }
+
+    #[test]
+    fn test_destroy_pointer_and_niche_types() {
+        let value = 7u32;
+        MustDestroy::new(Expect(&value)).destroy(&7);
+        MustDestroy::new(Expect(Box::new(7u32))).destroy(Box::new(7));
+        MustDestroy::new(Expect(NonNull::from(&value))).destroy(NonNull::from(&value));
+        MustDestroy::new(Expect(NonZeroU32::new(7).unwrap())).destroy(NonZeroU32::new(7).unwrap());
+        MustDestroy::new(Expect(vec![1, 2, 3])).destroy(vec![1, 2, 3]);
+        MustDestroy::new(Expect(Handle::Open(Box::new(7)))).destroy(Handle::Open(Box::new(7)));
+        MustDestroy::new(Expect(Handle::Closed)).destroy(Handle::Closed);
+    }
+
+    #[test]
+    fn test_into_inner_pointer_and_niche_types() {
+        let value = 7u32;
+        assert_eq!(&7, MustDestroy::new(&value).into_inner());
+        assert_eq!(Box::new(7), MustDestroy::new(Box::new(7)).into_inner());
+        assert_eq!(
+            NonNull::from(&value),
+            MustDestroy::new(NonNull::from(&value)).into_inner()
+        );
+        assert_eq!(
+            NonZeroU32::new(7),
+            Some(MustDestroy::new(NonZeroU32::new(7).unwrap()).into_inner())
+        );
+        assert_eq!(vec![1, 2, 3], MustDestroy::new(vec![1, 2, 3]).into_inner());
+        assert_eq!(
+            Handle::Open(Box::new(7)),
+            MustDestroy::new(Handle::Open(Box::new(7))).into_inner()
+        );
+    }
+
+    #[test]
+    fn test_into_inner_does_not_drop() {
+        let drops = Rc::new(Cell::new(0));
+        let counter = MustDestroy::new(DropCounter(drops.clone())).into_inner();
+        assert_eq!(0, drops.get());
+        drop(counter);
+        assert_eq!(1, drops.get());
+    }
+
+    #[test]
+    fn test_drop_panics_and_drops_wrapped_once() {
+        let drops = Rc::new(Cell::new(0));
+        let guard = MustDestroy::new(DropCounter(drops.clone()));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || drop(guard)));
+        assert!(result.is_err());
+        assert_eq!(1, drops.get());
+    }
+
+    #[test]
+    fn test_violation_names_type_and_creation_site() {
+        let (guard, line) = (MustDestroy::new(Handle::Closed), line!());
+        let payload = std::panic::catch_unwind(move || drop(guard)).unwrap_err();
+        let violation = payload.downcast_ref::<crate::Violation>().unwrap();
+        assert_eq!(std::any::type_name::<Handle>(), violation.type_name());
+        assert_eq!(crate::Reason::Dropped, violation.reason());
+        let message = violation.to_string();
+        assert!(message.contains(std::any::type_name::<Handle>()));
+        assert!(message.contains(&format!("{}:{}:", file!(), line)));
+    }
+
+    #[test]
+    fn test_drop_while_panicking_keeps_original_panic() {
+        let drops = Rc::new(Cell::new(0));
+        let panic_with_guard = || {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                let _guard = MustDestroy::new(DropCounter(drops.clone()));
+                panic!("original panic");
+            }))
+        };
+        let payload = panic_with_guard().unwrap_err();
+        assert_eq!(Some(&"original panic"), payload.downcast_ref::<&str>());
+        assert_eq!(1, drops.get());
+
+        let violations = crate::catch_violations(panic_with_guard).unwrap_err();
+        assert_eq!(1, violations.len());
+        assert_eq!(Reason::DroppedWhilePanicking, violations[0].reason());
+    }
+
     #[test]
     fn test_readme() {
         struct MyDestroyableItem;
@@ -0,0 +1,103 @@
+//! Where a guard was created, so violations can point back at it.
+use crate::scope::{Ledger, ScopeEntry};
+use crate::{catch, registry, DropPolicy, Violation};
+use std::any::type_name;
+#[cfg(feature = "backtrace")]
+use std::backtrace::Backtrace;
+use std::panic::Location;
+use std::sync::Arc;
+
+/// The creation site of a guard.
+///
+/// While it's alive, the guard is listed by the registry, if that was enabled when the
+/// guard was created, and by the scope it was created through, if any.
+#[derive(Debug)]
+pub(crate) struct Origin {
+    type_name: &'static str,
+    location: &'static Location<'static>,
+    #[cfg(feature = "backtrace")]
+    backtrace: Arc<Backtrace>,
+    registration: Option<u64>,
+    scope: Option<ScopeEntry>,
+}
+
+impl Origin {
+    /// Captures the location of the caller, which should be where a guard wrapping `T` is
+    /// created.
+    #[track_caller]
+    pub(crate) fn here<T>() -> Self {
+        let location = Location::caller();
+        Origin {
+            type_name: type_name::<T>(),
+            location,
+            #[cfg(feature = "backtrace")]
+            backtrace: Arc::new(Backtrace::force_capture()),
+            registration: registry::register(type_name::<T>(), location),
+            scope: None,
+        }
+    }
+
+    /// Makes the guard checked by a scope.
+    pub(crate) fn enter_scope(&mut self, entry: ScopeEntry) {
+        self.scope = Some(entry);
+    }
+
+    /// Stops the guard being checked by the scope owning `ledger`, if that's its scope.
+    pub(crate) fn leave_scope(&mut self, ledger: &Arc<Ledger>) {
+        if self
+            .scope
+            .as_ref()
+            .is_some_and(|entry| entry.belongs_to(ledger))
+        {
+            self.scope = None;
+        }
+    }
+
+    /// The ledger of the scope still checking the guard, if any, for reporting violations
+    /// on its behalf once the guard itself is out of reach.
+    pub(crate) fn scope(&self) -> Option<Arc<Ledger>> {
+        self.scope.as_ref().map(|entry| entry.ledger().clone())
+    }
+
+    /// Reports a violation by the guard, to its scope if it's still checked by one,
+    /// otherwise to the policy `P`.
+    pub(crate) fn report<P: DropPolicy>(&self, violation: Violation) {
+        match &self.scope {
+            Some(entry) if entry.report(&violation) => violation.record(),
+            _ => violation.raise::<P>(),
+        }
+    }
+
+    /// Reports a violation that was handled by the guard's fallback, to its scope if it's
+    /// still checked by one, otherwise to `catch_violations` if it's running, otherwise as a
+    /// warning on stderr.
+    pub(crate) fn warn(&self, violation: Violation) {
+        violation.record();
+        match &self.scope {
+            Some(entry) if entry.report(&violation) => {}
+            _ if catch::catch(&violation) => {}
+            _ => eprintln!("warning: {}", violation),
+        }
+    }
+
+    pub(crate) fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    pub(crate) fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    #[cfg(feature = "backtrace")]
+    pub(crate) fn backtrace(&self) -> &Arc<Backtrace> {
+        &self.backtrace
+    }
+}
+
+impl Drop for Origin {
+    fn drop(&mut self) {
+        if let Some(id) = self.registration {
+            registry::unregister(id);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Destruction in place, for pinned items that can't be moved to be destroyed.
+use crate::origin::Origin;
+use crate::{DefaultPolicy, DropPolicy, MustDestroy, Violation};
+use std::any::type_name;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::pin::Pin;
+
+/// Trait applied to items that are destroyed where they lie, such as self-referential or
+/// otherwise pinned items.
+///
+/// The item is still dropped afterwards, as pinned items must be.
+pub trait DestroyInPlace<Args> {
+    /// destroys the pinned item being called upon.
+    fn destroy_pinned(self: Pin<&mut Self>, args: Args);
+}
+
+/// A guard for a pinned item, which must be destroyed through `DestroyInPlace`.
+///
+/// Once destroyed, the item is dropped and the guard remembers it, so dropping the guard
+/// afterwards is fine and `item` returns `None`. Dropping it before then raises a violation.
+/// The item is dropped even if the policy would otherwise leak it, as a pinned item has to
+/// be dropped before its memory can be reused.
+pub struct PinnedMustDestroy<T, P: DropPolicy = DefaultPolicy> {
+    /// `None` once the item has been destroyed.
+    origin: Option<Origin>,
+    wrapped: ManuallyDrop<T>,
+    policy: PhantomData<fn() -> P>,
+}
+
+impl<T> MustDestroy<T> {
+    /// Create a new guard for the given item, pinned in a box, to be destroyed in place
+    #[track_caller]
+    pub fn pin(item: T) -> Pin<Box<PinnedMustDestroy<T>>> {
+        Box::pin(PinnedMustDestroy::new(item))
+    }
+}
+
+impl<T> PinnedMustDestroy<T> {
+    /// Create a new guard for the given item, which must be pinned before it's destroyed
+    #[track_caller]
+    pub fn new(item: T) -> Self {
+        PinnedMustDestroy::with_policy(item, DefaultPolicy)
+    }
+}
+
+impl<T, P: DropPolicy> PinnedMustDestroy<T, P> {
+    /// Create a new guard for the given item, using `policy` if it's dropped
+    #[track_caller]
+    pub fn with_policy(item: T, _policy: P) -> Self {
+        PinnedMustDestroy {
+            origin: Some(Origin::here::<T>()),
+            wrapped: ManuallyDrop::new(item),
+            policy: PhantomData,
+        }
+    }
+
+    /// Whether the item has already been destroyed.
+    pub fn is_destroyed(&self) -> bool {
+        self.origin.is_none()
+    }
+
+    /// The item, unless it has already been destroyed.
+    pub fn item(self: Pin<&Self>) -> Option<Pin<&T>> {
+        if self.is_destroyed() {
+            return None;
+        }
+        // Safe because the item is pinned along with the guard.
+        Some(unsafe { self.map_unchecked(|this| &*this.wrapped) })
+    }
+
+    /// The item, unless it has already been destroyed.
+    pub fn item_mut(self: Pin<&mut Self>) -> Option<Pin<&mut T>> {
+        if self.is_destroyed() {
+            return None;
+        }
+        // Safe because the item is pinned along with the guard, and never moved out.
+        Some(unsafe { self.map_unchecked_mut(|this| &mut *this.wrapped) })
+    }
+
+    /// destroys the item in place, then drops it.
+    ///
+    /// # Panics
+    ///
+    /// If the item has already been destroyed.
+    pub fn destroy<Args>(mut self: Pin<&mut Self>, args: Args)
+    where
+        T: DestroyInPlace<Args>,
+    {
+        match self.as_mut().item_mut() {
+            Some(item) => item.destroy_pinned(args),
+            None => panic!("`{}` was already destroyed", type_name::<T>()),
+        }
+        // Safe because the item is dropped where it lies, and marked as gone first so it's
+        // never touched again, even if dropping it panics.
+        unsafe {
+            let this = self.get_unchecked_mut();
+            let origin = this.origin.take();
+            ManuallyDrop::drop(&mut this.wrapped);
+            drop(origin);
+        }
+    }
+}
+
+impl<T, P: DropPolicy> Drop for PinnedMustDestroy<T, P> {
+    fn drop(&mut self) {
+        if let Some(origin) = self.origin.take() {
+            // Safe because the item was never destroyed, so this is the only drop, and
+            // `origin` being gone means it's never touched again.
+            unsafe { ManuallyDrop::drop(&mut self.wrapped) };
+            origin.report::<P>(Violation::dropped(&origin));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DestroyInPlace, MustDestroy, PinnedMustDestroy};
+    use std::cell::RefCell;
+    use std::marker::PhantomPinned;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::pin::{pin, Pin};
+
+    /// Points back at itself once pinned, and records whether it was destroyed before it
+    /// was dropped.
+    struct Node<'a> {
+        this: *const Node<'a>,
+        log: &'a RefCell<Vec<&'static str>>,
+        _pinned: PhantomPinned,
+    }
+
+    impl<'a> Node<'a> {
+        fn new(log: &'a RefCell<Vec<&'static str>>) -> Self {
+            Node {
+                this: std::ptr::null(),
+                log,
+                _pinned: PhantomPinned,
+            }
+        }
+
+        fn link(self: Pin<&mut Self>) {
+            // Safe because the node isn't moved.
+            let this = unsafe { self.get_unchecked_mut() };
+            this.this = this;
+        }
+    }
+
+    impl<'a> DestroyInPlace<u32> for Node<'a> {
+        fn destroy_pinned(self: Pin<&mut Self>, args: u32) {
+            assert!(std::ptr::eq(self.this, &*self));
+            assert_eq!(7, args);
+            self.log.borrow_mut().push("destroyed");
+        }
+    }
+
+    impl<'a> Drop for Node<'a> {
+        fn drop(&mut self) {
+            self.log.borrow_mut().push("dropped");
+        }
+    }
+
+    #[test]
+    fn test_boxed() {
+        let log = RefCell::new(Vec::new());
+        let mut node = MustDestroy::pin(Node::new(&log));
+        node.as_mut().item_mut().unwrap().link();
+        node.as_mut().destroy(7);
+        assert!(node.is_destroyed());
+        assert!(node.as_ref().item().is_none());
+        drop(node);
+        assert_eq!(vec!["destroyed", "dropped"], log.into_inner());
+    }
+
+    #[test]
+    fn test_stack_pinned() {
+        let log = RefCell::new(Vec::new());
+        let mut node = pin!(PinnedMustDestroy::new(Node::new(&log)));
+        node.as_mut().item_mut().unwrap().link();
+        node.as_mut().destroy(7);
+        assert_eq!(vec!["destroyed", "dropped"], *log.borrow());
+    }
+
+    #[test]
+    #[should_panic(expected = "already destroyed")]
+    fn test_destroy_twice_panics() {
+        let log = RefCell::new(Vec::new());
+        let mut node = MustDestroy::pin(Node::new(&log));
+        node.as_mut().item_mut().unwrap().link();
+        node.as_mut().destroy(7);
+        node.as_mut().destroy(7);
+    }
+
+    #[test]
+    fn test_dropping_is_violation() {
+        let log = RefCell::new(Vec::new());
+        let node = PinnedMustDestroy::new(Node::new(&log));
+        let panicked = catch_unwind(AssertUnwindSafe(move || drop(node))).is_err();
+        assert!(panicked);
+        // The node is dropped before the policy panics.
+        assert_eq!(vec!["dropped"], log.into_inner());
+    }
+}
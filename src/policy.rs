@@ -0,0 +1,208 @@
+//! Policies deciding what happens when a `MustDestroy` guard is dropped.
+use crate::Violation;
+use std::env;
+use std::panic;
+use std::sync::OnceLock;
+
+/// What to do with the wrapped value of a guard that was dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Disposal {
+    /// Run the wrapped value's own `Drop`.
+    Drop,
+    /// Never run the wrapped value's `Drop`, leaking it.
+    Leak,
+}
+
+/// Decides how a `MustDestroy` guard reacts to being dropped without being destroyed.
+pub trait DropPolicy {
+    /// How the wrapped value is disposed of. This happens before `on_violation` is called.
+    fn disposal() -> Disposal {
+        Disposal::Drop
+    }
+
+    /// Called when a guard using this policy is dropped.
+    fn on_violation(violation: &Violation);
+
+    /// Called instead of `on_violation` when the guard is dropped while the thread is
+    /// already panicking.
+    ///
+    /// Panicking again here would abort the process and lose the original panic, so by
+    /// default this only reports to stderr and lets the original panic continue.
+    fn on_violation_while_panicking(violation: &Violation) {
+        eprintln!("{}", violation);
+    }
+}
+
+/// Acts as `PanicPolicy`, `AbortPolicy` or `LogPolicy`, or ignores violations, depending on
+/// whether the `MUST_DESTROY_ON_VIOLATION` environment variable is `panic`, `abort`, `log`
+/// or `ignore`. This is the default policy.
+///
+/// The variable is read once, at the first violation. When it's unset or unrecognised,
+/// this panics.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultPolicy;
+
+/// What `DefaultPolicy` does, as configured by the environment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OnViolation {
+    Panic,
+    Abort,
+    Log,
+    Ignore,
+}
+
+impl OnViolation {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "panic" => Some(OnViolation::Panic),
+            "abort" => Some(OnViolation::Abort),
+            "log" => Some(OnViolation::Log),
+            "ignore" => Some(OnViolation::Ignore),
+            _ => None,
+        }
+    }
+
+    fn configured() -> Self {
+        static CONFIGURED: OnceLock<OnViolation> = OnceLock::new();
+        *CONFIGURED.get_or_init(|| match env::var("MUST_DESTROY_ON_VIOLATION") {
+            Ok(value) => OnViolation::parse(&value).unwrap_or_else(|| {
+                eprintln!(
+                    "MUST_DESTROY_ON_VIOLATION should be panic, abort, log or ignore, not `{}`",
+                    value
+                );
+                OnViolation::Panic
+            }),
+            Err(_) => OnViolation::Panic,
+        })
+    }
+}
+
+impl DropPolicy for DefaultPolicy {
+    fn on_violation(violation: &Violation) {
+        match OnViolation::configured() {
+            OnViolation::Panic => PanicPolicy::on_violation(violation),
+            OnViolation::Abort => AbortPolicy::on_violation(violation),
+            OnViolation::Log => LogPolicy::on_violation(violation),
+            OnViolation::Ignore => {}
+        }
+    }
+
+    fn on_violation_while_panicking(violation: &Violation) {
+        match OnViolation::configured() {
+            OnViolation::Panic => PanicPolicy::on_violation_while_panicking(violation),
+            OnViolation::Abort => AbortPolicy::on_violation_while_panicking(violation),
+            OnViolation::Log => LogPolicy::on_violation_while_panicking(violation),
+            OnViolation::Ignore => {}
+        }
+    }
+}
+
+/// Panics when dropped, with the `Violation` as the panic's payload.
+///
+/// The violation is also reported to stderr, as the default panic hook can only show
+/// string payloads.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PanicPolicy;
+
+impl DropPolicy for PanicPolicy {
+    fn on_violation(violation: &Violation) {
+        eprintln!("{}", violation);
+        panic::panic_any(violation.clone());
+    }
+}
+
+/// Reports to stderr and aborts the process when dropped.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AbortPolicy;
+
+impl DropPolicy for AbortPolicy {
+    fn on_violation(violation: &Violation) {
+        eprintln!("{}", violation);
+        std::process::abort();
+    }
+
+    fn on_violation_while_panicking(violation: &Violation) {
+        Self::on_violation(violation);
+    }
+}
+
+/// Reports to stderr and carries on when dropped, dropping the wrapped value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogPolicy;
+
+impl DropPolicy for LogPolicy {
+    fn on_violation(violation: &Violation) {
+        eprintln!("{}", violation);
+    }
+}
+
+/// Silently leaks the wrapped value when dropped.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LeakPolicy;
+
+impl DropPolicy for LeakPolicy {
+    fn disposal() -> Disposal {
+        Disposal::Leak
+    }
+
+    fn on_violation(_violation: &Violation) {}
+
+    fn on_violation_while_panicking(_violation: &Violation) {}
+}
+
+/// Panics when dropped in debug builds, behaves like `LogPolicy` in release builds.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DebugPanicPolicy;
+
+impl DropPolicy for DebugPanicPolicy {
+    fn on_violation(violation: &Violation) {
+        if cfg!(debug_assertions) {
+            PanicPolicy::on_violation(violation);
+        } else {
+            LogPolicy::on_violation(violation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OnViolation;
+    use crate::{DebugPanicPolicy, DropPolicy, LeakPolicy, LogPolicy, MustDestroy, PanicPolicy};
+    use std::cell::Cell;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    struct DropCounter(Rc<Cell<usize>>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    /// Drops a guard using the given policy, returning whether it panicked and how many
+    /// times the wrapped value was dropped.
+    fn drop_with<P: DropPolicy>(policy: P) -> (bool, usize) {
+        let drops = Rc::new(Cell::new(0));
+        let guard = MustDestroy::with_policy(DropCounter(drops.clone()), policy);
+        let panicked = catch_unwind(AssertUnwindSafe(move || drop(guard))).is_err();
+        (panicked, drops.get())
+    }
+
+    #[test]
+    fn test_policies() {
+        assert_eq!((true, 1), drop_with(PanicPolicy));
+        assert_eq!((false, 1), drop_with(LogPolicy));
+        assert_eq!((false, 0), drop_with(LeakPolicy));
+        assert_eq!((cfg!(debug_assertions), 1), drop_with(DebugPanicPolicy));
+    }
+
+    #[test]
+    fn test_default_policy_configuration() {
+        assert_eq!(Some(OnViolation::Panic), OnViolation::parse("panic"));
+        assert_eq!(Some(OnViolation::Abort), OnViolation::parse("abort"));
+        assert_eq!(Some(OnViolation::Log), OnViolation::parse(" LOG\n"));
+        assert_eq!(Some(OnViolation::Ignore), OnViolation::parse("ignore"));
+        assert_eq!(None, OnViolation::parse("explode"));
+    }
+}
@@ -0,0 +1,316 @@
+//! A pool of items that must be destroyed, handed out through checkouts that must be
+//! returned or destroyed.
+use crate::held::Held;
+use crate::impls::destroy_each;
+use crate::origin::Origin;
+use crate::scope::Ledger;
+use crate::sync::lock;
+use crate::{DefaultPolicy, Destroy, DropPolicy, MustDestroy, Reason, Violation};
+use std::collections::BTreeMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+/// The violation to raise for a checkout if it's still out when the pool is destroyed.
+struct Outstanding {
+    violation: Violation,
+    /// The scope still checking the checkout's guard, if any, which takes the violation.
+    scope: Option<Arc<Ledger>>,
+}
+
+struct PoolState<T, P: DropPolicy> {
+    idle: Vec<Held<T, P>>,
+    checked_out: BTreeMap<u64, Outstanding>,
+    next_id: u64,
+    closed: bool,
+}
+
+type Shared<T, P> = Arc<Mutex<PoolState<T, P>>>;
+
+/// Owns items that must be destroyed, lending them out to be reused.
+///
+/// Each `Checkout` must either be released back to the pool or destroyed. Destroying the
+/// pool destroys every idle item, and raises a violation for each checkout still out.
+pub struct DestroyPool<T, P: DropPolicy = DefaultPolicy> {
+    shared: Shared<T, P>,
+}
+
+impl<T> DestroyPool<T> {
+    /// Create a new, empty `DestroyPool`
+    pub fn new() -> Self {
+        DestroyPool::with_policy(DefaultPolicy)
+    }
+}
+
+impl<T> Default for DestroyPool<T> {
+    fn default() -> Self {
+        DestroyPool::new()
+    }
+}
+
+impl<T, P: DropPolicy> DestroyPool<T, P> {
+    /// Create a new, empty `DestroyPool`, whose items use `policy` if they're dropped
+    pub fn with_policy(_policy: P) -> Self {
+        DestroyPool {
+            shared: Arc::new(Mutex::new(PoolState {
+                idle: Vec::new(),
+                checked_out: BTreeMap::new(),
+                next_id: 0,
+                closed: false,
+            })),
+        }
+    }
+
+    /// Adds an idle item, recording the caller as where its guard was created.
+    #[track_caller]
+    pub fn add(&self, item: T) {
+        self.add_guard(MustDestroy::arm(item, Origin::here::<T>()));
+    }
+
+    /// Adds an idle item that is already guarded.
+    pub fn add_guard(&self, guard: MustDestroy<T, P>) {
+        lock(&self.shared).idle.push(Held::new(guard));
+    }
+
+    /// The number of items waiting in the pool.
+    pub fn idle(&self) -> usize {
+        lock(&self.shared).idle.len()
+    }
+
+    /// The number of items currently checked out of the pool.
+    pub fn checked_out(&self) -> usize {
+        lock(&self.shared).checked_out.len()
+    }
+
+    /// Lends out the most recently returned idle item, if there is one.
+    pub fn checkout(&self) -> Option<Checkout<T, P>> {
+        let mut state = lock(&self.shared);
+        let item = state.idle.pop()?;
+        let id = state.next_id;
+        state.next_id += 1;
+        let origin = &item.guard().origin;
+        let outstanding = Outstanding {
+            violation: Violation::new(origin, Reason::CheckedOut),
+            scope: origin.scope(),
+        };
+        state.checked_out.insert(id, outstanding);
+        Some(Checkout {
+            item: Some(item),
+            shared: self.shared.clone(),
+            id,
+        })
+    }
+
+    /// Closes the pool, taking out its idle items and the checkouts still out.
+    fn close(&self) -> (Vec<Held<T, P>>, BTreeMap<u64, Outstanding>) {
+        let mut state = lock(&self.shared);
+        state.closed = true;
+        (
+            std::mem::take(&mut state.idle),
+            std::mem::take(&mut state.checked_out),
+        )
+    }
+}
+
+impl<Args: Clone, T: Destroy<Args>, P: DropPolicy> Destroy<Args> for DestroyPool<T, P> {
+    /// destroys every idle item with a clone of the arguments, then raises a violation for
+    /// each checkout still out
+    fn destroy(self, args: Args) {
+        let (idle, checked_out) = self.close();
+        destroy_each(idle, args);
+        let mut unreported = Vec::new();
+        for Outstanding { violation, scope } in checked_out.into_values() {
+            match scope {
+                Some(ledger) if ledger.report(&violation) => violation.record(),
+                _ => unreported.push(violation),
+            }
+        }
+        Violation::raise_all::<P>(unreported);
+    }
+}
+
+impl<T, P: DropPolicy> Drop for DestroyPool<T, P> {
+    fn drop(&mut self) {
+        if !lock(&self.shared).closed {
+            // Dropped outside the lock, as each idle item raises a violation.
+            drop(self.close());
+        }
+    }
+}
+
+/// An item lent out by a `DestroyPool`.
+///
+/// It must be released back to the pool, or destroyed. Dropping it raises a violation,
+/// just like dropping a `MustDestroy` would.
+pub struct Checkout<T, P: DropPolicy = DefaultPolicy> {
+    item: Option<Held<T, P>>,
+    shared: Shared<T, P>,
+    id: u64,
+}
+
+impl<T, P: DropPolicy> Checkout<T, P> {
+    /// Takes the item out of the checkout, so the pool no longer waits for it.
+    fn take(&mut self) -> Held<T, P> {
+        lock(&self.shared).checked_out.remove(&self.id);
+        self.item.take().expect("checkout already taken")
+    }
+
+    /// Returns the item to the pool so it can be checked out again.
+    ///
+    /// If the pool has already been destroyed or dropped, the item is handed back as a
+    /// guard to be destroyed instead.
+    pub fn release(mut self) -> Result<(), MustDestroy<T, P>> {
+        let item = self.take();
+        let mut state = lock(&self.shared);
+        if state.closed {
+            Err(item.into_guard())
+        } else {
+            state.idle.push(item);
+            Ok(())
+        }
+    }
+
+    /// Takes the item out of the pool for good, handing it back as a guard.
+    pub fn into_guard(mut self) -> MustDestroy<T, P> {
+        self.take().into_guard()
+    }
+}
+
+impl<Args, T: Destroy<Args>, P: DropPolicy> Destroy<Args> for Checkout<T, P> {
+    /// destroys the item, taking it out of the pool for good
+    fn destroy(self, args: Args) {
+        self.into_guard().destroy(args);
+    }
+}
+
+impl<T, P: DropPolicy> Deref for Checkout<T, P> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.item.as_ref().expect("checkout already taken").get()
+    }
+}
+
+impl<T, P: DropPolicy> DerefMut for Checkout<T, P> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.item
+            .as_mut()
+            .expect("checkout already taken")
+            .get_mut()
+    }
+}
+
+impl<T, P: DropPolicy> Drop for Checkout<T, P> {
+    fn drop(&mut self) {
+        if self.item.is_some() {
+            // The item raises a violation as it's dropped.
+            drop(self.take());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{catch_violations, scope, Destroy, DestroyPool, Reason};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::sync::{Arc, Mutex};
+
+    /// Records its id to the log when destroyed.
+    struct Connection(u32, Arc<Mutex<Vec<u32>>>);
+
+    impl Destroy<()> for Connection {
+        fn destroy(self, _args: ()) {
+            self.1.lock().unwrap().push(self.0);
+        }
+    }
+
+    #[test]
+    fn test_pool() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let pool = DestroyPool::new();
+        pool.add(Connection(1, log.clone()));
+        pool.add(Connection(2, log.clone()));
+
+        let connection = pool.checkout().unwrap();
+        assert_eq!(2, connection.0);
+        assert_eq!((1, 1), (pool.idle(), pool.checked_out()));
+        connection.release().ok().unwrap();
+
+        let reused = pool.checkout().unwrap();
+        assert_eq!(2, reused.0);
+        reused.destroy(());
+        assert_eq!((1, 0), (pool.idle(), pool.checked_out()));
+
+        pool.destroy(());
+        assert_eq!(vec![2, 1], *log.lock().unwrap());
+    }
+
+    #[test]
+    fn test_destroying_pool_reports_checked_out() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let pool = DestroyPool::new();
+        for id in 1..=3 {
+            pool.add(Connection(id, log.clone()));
+        }
+        let connection = pool.checkout().unwrap();
+        let other = pool.checkout().unwrap();
+
+        let violations = catch_violations(|| pool.destroy(())).unwrap_err();
+        assert_eq!(2, violations.len());
+        assert!(violations
+            .iter()
+            .all(|violation| violation.reason() == Reason::CheckedOut));
+        assert_eq!(vec![1], *log.lock().unwrap());
+
+        // With the pool gone, the checkouts have to be destroyed themselves.
+        connection.release().unwrap_err().destroy();
+        other.release().unwrap_err().destroy();
+        assert_eq!(vec![1, 3, 2], *log.lock().unwrap());
+    }
+
+    #[test]
+    fn test_destroying_pool_panics_on_checked_out() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let pool = DestroyPool::new();
+        pool.add(Connection(1, log.clone()));
+        let connection = pool.checkout().unwrap();
+
+        let panicked = catch_unwind(AssertUnwindSafe(|| pool.destroy(()))).is_err();
+        assert!(panicked);
+        connection.release().unwrap_err().destroy();
+        assert_eq!(vec![1], *log.lock().unwrap());
+    }
+
+    #[test]
+    fn test_checked_out_reported_to_scope() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let violations = scope(|s| {
+            let pool = DestroyPool::new();
+            pool.add_guard(s.guard(Connection(1, log.clone())));
+            pool.add_guard(s.guard(Connection(2, log.clone())));
+            let checkouts: Vec<_> = (0..2).filter_map(|_| pool.checkout()).collect();
+            pool.destroy(());
+            for checkout in checkouts {
+                checkout.release().unwrap_err().destroy();
+            }
+        })
+        .unwrap_err();
+        assert_eq!(2, violations.len());
+        assert!(violations
+            .iter()
+            .all(|violation| violation.reason() == Reason::CheckedOut));
+    }
+
+    #[test]
+    fn test_dropping_checkout_is_violation() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let violations = scope(|s| {
+            let pool = DestroyPool::new();
+            pool.add_guard(s.guard(Connection(1, log.clone())));
+            drop(pool.checkout());
+            pool.destroy(());
+        })
+        .unwrap_err();
+        assert_eq!(1, violations.len());
+        assert_eq!(Reason::Dropped, violations[0].reason());
+    }
+}
@@ -0,0 +1,134 @@
+//! Opt-in, process-wide tracking of every guard that is still waiting to be destroyed.
+//!
+//! Once `enable` has been called, every guard created afterwards is registered until it
+//! is destroyed, has `into_inner` called, or is dropped. `outstanding` lists whatever is
+//! still registered, which includes guards that were leaked with `mem::forget`.
+use crate::sync::lock;
+use std::collections::BTreeMap;
+use std::panic::Location;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static LIVE: Mutex<BTreeMap<u64, Obligation>> = Mutex::new(BTreeMap::new());
+
+/// A guard that has been created but not yet destroyed.
+#[derive(Clone, Debug)]
+pub struct Obligation {
+    type_name: &'static str,
+    location: &'static Location<'static>,
+    created: Instant,
+}
+
+impl Obligation {
+    /// The name of the type wrapped by the guard.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Where the guard was created.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// When the guard was created.
+    pub fn created(&self) -> Instant {
+        self.created
+    }
+
+    /// How long ago the guard was created.
+    pub fn age(&self) -> Duration {
+        self.created.elapsed()
+    }
+}
+
+/// Starts registering guards as they are created.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Stops registering new guards. Guards that are already registered stay registered until
+/// they are destroyed.
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+/// Whether guards are currently registered as they are created.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Every registered guard that has not been destroyed yet, oldest first.
+pub fn outstanding() -> Vec<Obligation> {
+    live().values().cloned().collect()
+}
+
+fn live() -> MutexGuard<'static, BTreeMap<u64, Obligation>> {
+    lock(&LIVE)
+}
+
+/// Registers a new guard if tracking is enabled, returning its id.
+pub(crate) fn register(
+    type_name: &'static str,
+    location: &'static Location<'static>,
+) -> Option<u64> {
+    if !is_enabled() {
+        return None;
+    }
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let obligation = Obligation {
+        type_name,
+        location,
+        created: Instant::now(),
+    };
+    live().insert(id, obligation);
+    Some(id)
+}
+
+/// Removes a guard registered with `register`.
+pub(crate) fn unregister(id: u64) {
+    live().remove(&id);
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::sync::lock;
+    use crate::{registry, LogPolicy, MustDestroy};
+    use std::any::type_name;
+    use std::sync::Mutex;
+
+    /// Held by tests enabling the registry, which disable it again before letting go.
+    pub(crate) static ENABLING: Mutex<()> = Mutex::new(());
+
+    struct Tracked;
+
+    fn outstanding_tracked() -> Vec<registry::Obligation> {
+        registry::outstanding()
+            .into_iter()
+            .filter(|obligation| obligation.type_name() == type_name::<Tracked>())
+            .collect()
+    }
+
+    #[test]
+    fn test_registry() {
+        let _enabling = lock(&ENABLING);
+        registry::enable();
+        let (into_inner, line) = (MustDestroy::new(Tracked), line!());
+        let dropped = MustDestroy::with_policy(Tracked, LogPolicy);
+        let forgotten = MustDestroy::new(Tracked);
+
+        let outstanding = outstanding_tracked();
+        assert_eq!(3, outstanding.len());
+        assert_eq!(file!(), outstanding[0].location().file());
+        assert_eq!(line, outstanding[0].location().line());
+        assert!(outstanding[0].age() <= outstanding[0].created().elapsed());
+
+        into_inner.into_inner();
+        drop(dropped);
+        std::mem::forget(forgotten);
+        assert_eq!(1, outstanding_tracked().len());
+        registry::disable();
+    }
+}
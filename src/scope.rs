@@ -0,0 +1,234 @@
+//! Scopes that check every guard created within them was destroyed.
+use crate::origin::Origin;
+use crate::sync::lock;
+use crate::{DefaultPolicy, DropPolicy, MustDestroy, Reason, Violation};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Runs `f` with a `Scope`, checking that every guard created through it was destroyed or
+/// escaped by the time `f` returns.
+///
+/// Rather than going to their `DropPolicy`, violations by guards created through the scope
+/// are collected and returned as the error. This includes guards that were leaked or
+/// forgotten with `mem::forget`, which never get the chance to report themselves.
+///
+/// ```
+/// use must_destroy::{scope, Destroy, Reason};
+///
+/// struct Buffer;
+///
+/// impl Destroy<()> for Buffer {
+///     fn destroy(self, _args: ()) {}
+/// }
+///
+/// let violations = scope(|s| {
+///     s.guard(Buffer).destroy();
+///     std::mem::forget(s.guard(Buffer));
+/// })
+/// .unwrap_err();
+/// assert_eq!(1, violations.len());
+/// assert_eq!(Reason::Leaked, violations[0].reason());
+/// ```
+pub fn scope<R, F: FnOnce(&Scope) -> R>(f: F) -> Result<R, Vec<Violation>> {
+    let scope = Scope {
+        ledger: Arc::new(Ledger::default()),
+    };
+    let result = f(&scope);
+
+    let mut state = scope.ledger.state();
+    state.closed = true;
+    let mut violations = std::mem::take(&mut state.violations);
+    for violation in std::mem::take(&mut state.live).into_values() {
+        violation.record();
+        violations.push(violation);
+    }
+
+    if violations.is_empty() {
+        Ok(result)
+    } else {
+        Err(violations)
+    }
+}
+
+/// Handle for creating guards that are checked by a `scope`.
+pub struct Scope {
+    ledger: Arc<Ledger>,
+}
+
+impl Scope {
+    /// Create a new `MustDestroy` for the given item, checked by this scope
+    #[track_caller]
+    pub fn guard<T>(&self, item: T) -> MustDestroy<T> {
+        self.guard_with_policy(item, DefaultPolicy)
+    }
+
+    /// Create a new `MustDestroy` for the given item, checked by this scope. `policy` only
+    /// applies once the guard has been escaped from the scope.
+    #[track_caller]
+    pub fn guard_with_policy<T, P: DropPolicy>(&self, item: T, _policy: P) -> MustDestroy<T, P> {
+        let mut origin = Origin::here::<T>();
+        origin.enter_scope(ScopeEntry::new(&self.ledger, &origin));
+        MustDestroy::arm(item, origin)
+    }
+
+    /// Create a new `MustDestroy` for the given item, checked by this scope, calling
+    /// `fallback` with it if it's dropped. The drop is still reported to the scope.
+    #[track_caller]
+    pub fn guard_with_fallback<T>(&self, item: T, fallback: fn(T)) -> MustDestroy<T> {
+        let mut guard = self.guard(item);
+        guard.set_fallback(fallback);
+        guard
+    }
+
+    /// Hands a guard created through this scope out of it, so it's no longer checked by the
+    /// scope and falls back to its `DropPolicy`.
+    ///
+    /// Guards that were not created through this scope are returned untouched.
+    pub fn escape<T, P: DropPolicy>(&self, mut guard: MustDestroy<T, P>) -> MustDestroy<T, P> {
+        guard.origin.leave_scope(&self.ledger);
+        guard
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Ledger {
+    state: Mutex<LedgerState>,
+}
+
+#[derive(Default)]
+struct LedgerState {
+    next_id: u64,
+    /// Guards still waiting to be destroyed, as the violation to report if they never are.
+    live: BTreeMap<u64, Violation>,
+    violations: Vec<Violation>,
+    closed: bool,
+}
+
+impl Ledger {
+    fn state(&self) -> MutexGuard<'_, LedgerState> {
+        lock(&self.state)
+    }
+
+    /// Reports a violation to the scope, returning `false` if the scope already ended.
+    pub(crate) fn report(&self, violation: &Violation) -> bool {
+        let mut state = self.state();
+        if state.closed {
+            return false;
+        }
+        state.violations.push(violation.clone());
+        true
+    }
+}
+
+impl fmt::Debug for Ledger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ledger").finish_non_exhaustive()
+    }
+}
+
+/// Membership of a guard in a scope, removed from the scope when dropped.
+#[derive(Debug)]
+pub(crate) struct ScopeEntry {
+    ledger: Arc<Ledger>,
+    id: u64,
+}
+
+impl ScopeEntry {
+    fn new(ledger: &Arc<Ledger>, origin: &Origin) -> Self {
+        let mut state = ledger.state();
+        let id = state.next_id;
+        state.next_id += 1;
+        state
+            .live
+            .insert(id, Violation::new(origin, Reason::Leaked));
+        ScopeEntry {
+            ledger: ledger.clone(),
+            id,
+        }
+    }
+
+    pub(crate) fn belongs_to(&self, ledger: &Arc<Ledger>) -> bool {
+        Arc::ptr_eq(&self.ledger, ledger)
+    }
+
+    pub(crate) fn ledger(&self) -> &Arc<Ledger> {
+        &self.ledger
+    }
+
+    /// Reports a violation to the scope, returning `false` if the scope already ended.
+    pub(crate) fn report(&self, violation: &Violation) -> bool {
+        self.ledger.report(violation)
+    }
+}
+
+impl Drop for ScopeEntry {
+    fn drop(&mut self) {
+        self.ledger.state().live.remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{scope, Destroy, MustDestroy, Reason};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    struct Buffer;
+
+    impl Destroy<()> for Buffer {
+        fn destroy(self, _args: ()) {}
+    }
+
+    #[test]
+    fn test_scope_ok() {
+        let escaped = scope(|s| {
+            s.guard(Buffer).destroy();
+            s.guard(Buffer).into_inner();
+            let escaped = s.guard(Buffer);
+            s.escape(escaped)
+        })
+        .unwrap();
+        escaped.destroy();
+    }
+
+    #[test]
+    fn test_scope_reports_violations() {
+        let (line, violations) = (
+            line!(),
+            scope(|s| {
+                std::mem::forget(s.guard(Buffer));
+                drop(s.guard(Buffer));
+                // Escaping a guard from outside the scope does nothing.
+                s.escape(MustDestroy::new(Buffer)).destroy();
+            })
+            .unwrap_err(),
+        );
+        assert_eq!(2, violations.len());
+        assert_eq!(Reason::Dropped, violations[0].reason());
+        assert_eq!(Reason::Leaked, violations[1].reason());
+        for violation in &violations {
+            assert_eq!(file!(), violation.location().file());
+            assert!(violation.location().line() > line);
+        }
+    }
+
+    #[test]
+    fn test_scope_lets_panic_through() {
+        let payload = catch_unwind(AssertUnwindSafe(|| {
+            scope(|s| {
+                let _guard = s.guard(Buffer);
+                panic!("original panic");
+            })
+        }))
+        .unwrap_err();
+        assert_eq!(Some(&"original panic"), payload.downcast_ref::<&str>());
+    }
+
+    #[test]
+    fn test_guard_outliving_scope_uses_policy() {
+        let mut leaked = None;
+        let violations = scope(|s| leaked = Some(s.guard(Buffer))).unwrap_err();
+        assert_eq!(Reason::Leaked, violations[0].reason());
+        assert!(catch_unwind(AssertUnwindSafe(move || drop(leaked))).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+//! Reference counted guards, where only the last owner must destroy the item.
+use crate::held::Held;
+use crate::origin::Origin;
+use crate::{DefaultPolicy, Destroy, DropPolicy, MustDestroy};
+use std::ops::Deref;
+use std::sync::{Arc, Weak};
+
+/// A thread-safe, reference counted `MustDestroy`.
+///
+/// Clones can be dropped freely, but the last one must be destroyed, or unwrapped back into
+/// a `MustDestroy`.
+pub struct SharedMustDestroy<T, P: DropPolicy = DefaultPolicy>(Arc<Held<T, P>>);
+
+impl<T> SharedMustDestroy<T> {
+    /// Create a new `SharedMustDestroy` for the given item
+    #[track_caller]
+    pub fn new(item: T) -> Self {
+        SharedMustDestroy::from_guard(MustDestroy::arm(item, Origin::here::<T>()))
+    }
+}
+
+impl<T, P: DropPolicy> SharedMustDestroy<T, P> {
+    /// Shares an existing guard.
+    pub fn from_guard(guard: MustDestroy<T, P>) -> Self {
+        SharedMustDestroy(Arc::new(Held::new(guard)))
+    }
+
+    /// Takes the guard back out if this is the only strong reference, otherwise hands this
+    /// reference back.
+    pub fn try_unwrap(self) -> Result<MustDestroy<T, P>, Self> {
+        Arc::try_unwrap(self.0)
+            .map(Held::into_guard)
+            .map_err(SharedMustDestroy)
+    }
+
+    /// Takes the guard back out if this is the last strong reference, otherwise just
+    /// releases it.
+    ///
+    /// Unlike `try_unwrap`, when several owners race to call this, exactly one of them gets
+    /// the guard.
+    pub fn into_inner(self) -> Option<MustDestroy<T, P>> {
+        Arc::into_inner(self.0).map(Held::into_guard)
+    }
+
+    /// Creates a weak reference, which doesn't keep the item alive.
+    pub fn downgrade(this: &Self) -> WeakMustDestroy<T, P> {
+        WeakMustDestroy(Arc::downgrade(&this.0))
+    }
+
+    /// The number of strong references to the item.
+    pub fn strong_count(this: &Self) -> usize {
+        Arc::strong_count(&this.0)
+    }
+
+    /// Whether both references point at the same item.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+impl<Args, T: Destroy<Args>, P: DropPolicy> Destroy<Args> for SharedMustDestroy<T, P> {
+    /// destroys the item if this is the last strong reference, otherwise just releases it
+    fn destroy(self, args: Args) {
+        if let Some(guard) = self.into_inner() {
+            guard.destroy(args);
+        }
+    }
+}
+
+impl<T, P: DropPolicy> Clone for SharedMustDestroy<T, P> {
+    fn clone(&self) -> Self {
+        SharedMustDestroy(self.0.clone())
+    }
+}
+
+impl<T, P: DropPolicy> Deref for SharedMustDestroy<T, P> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.0.get()
+    }
+}
+
+/// A weak reference to the item of a `SharedMustDestroy`.
+pub struct WeakMustDestroy<T, P: DropPolicy = DefaultPolicy>(Weak<Held<T, P>>);
+
+impl<T, P: DropPolicy> WeakMustDestroy<T, P> {
+    /// A strong reference to the item, if it hasn't been destroyed or dropped yet.
+    pub fn upgrade(&self) -> Option<SharedMustDestroy<T, P>> {
+        self.0.upgrade().map(SharedMustDestroy)
+    }
+}
+
+impl<T, P: DropPolicy> Clone for WeakMustDestroy<T, P> {
+    fn clone(&self) -> Self {
+        WeakMustDestroy(self.0.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{scope, Destroy, Reason, SharedMustDestroy};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    /// Counts how many times it was destroyed.
+    struct Device<'a>(&'a AtomicUsize);
+
+    impl<'a> Destroy<()> for Device<'a> {
+        fn destroy(self, _args: ()) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_shared() {
+        let destroyed = AtomicUsize::new(0);
+        let device = SharedMustDestroy::new(Device(&destroyed));
+        let weak = SharedMustDestroy::downgrade(&device);
+        let other = weak.upgrade().unwrap();
+        assert!(SharedMustDestroy::ptr_eq(&device, &other));
+        assert_eq!(2, SharedMustDestroy::strong_count(&device));
+
+        let device = device.try_unwrap().err().unwrap();
+        drop(other);
+        device.try_unwrap().ok().unwrap().destroy();
+        assert_eq!(1, destroyed.load(Ordering::SeqCst));
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_concurrent_clones() {
+        let destroyed = AtomicUsize::new(0);
+        let violations = scope(|s| {
+            let device = SharedMustDestroy::from_guard(s.guard(Device(&destroyed)));
+            // Every reference is moved into a thread, so one of them is the last owner.
+            let mut devices: Vec<_> = (1..8).map(|_| device.clone()).collect();
+            devices.push(device);
+            thread::scope(|threads| {
+                for device in devices {
+                    threads.spawn(move || {
+                        for _ in 0..1000 {
+                            drop(device.clone());
+                        }
+                        device.destroy(());
+                    });
+                }
+            });
+        });
+        assert!(violations.is_ok());
+        assert_eq!(1, destroyed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_dropping_last_reference_is_violation() {
+        let destroyed = AtomicUsize::new(0);
+        let violations = scope(|s| {
+            let device = SharedMustDestroy::from_guard(s.guard(Device(&destroyed)));
+            let clones: Vec<_> = (0..4).map(|_| device.clone()).collect();
+            drop(device);
+            drop(clones);
+        })
+        .unwrap_err();
+        assert_eq!(1, violations.len());
+        assert_eq!(Reason::Dropped, violations[0].reason());
+        assert_eq!(0, destroyed.load(Ordering::SeqCst));
+    }
+}
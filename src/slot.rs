@@ -0,0 +1,256 @@
+//! A field type holding a guard that can be destroyed through `&mut self`.
+use crate::held::Held;
+use crate::origin::Origin;
+use crate::{DefaultPolicy, Destroy, DropPolicy, MustDestroy};
+use std::any::type_name;
+use std::error::Error;
+use std::fmt;
+
+/// Why a `DestroySlot` has no item to hand out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotError {
+    /// The slot was never filled, or its guard was taken out.
+    Empty {
+        /// The name of the type the slot holds.
+        type_name: &'static str,
+    },
+    /// The slot's item was destroyed by `destroy_in_place`.
+    Destroyed {
+        /// The name of the type the slot holds.
+        type_name: &'static str,
+    },
+}
+
+impl fmt::Display for SlotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlotError::Empty { type_name } => write!(f, "slot for `{}` is empty", type_name),
+            SlotError::Destroyed { type_name } => {
+                write!(f, "`{}` in the slot was already destroyed", type_name)
+            }
+        }
+    }
+}
+
+impl Error for SlotError {}
+
+enum State<T, P: DropPolicy> {
+    Empty,
+    Armed(Held<T, P>),
+    Destroyed,
+}
+
+/// Holds a guard as a struct field, so it can be destroyed from a `&mut self` method.
+///
+/// An armed slot must be destroyed, like the [`MustDestroy`] it holds. Once destroyed, the
+/// slot remembers it, and accessing the item gives `SlotError::Destroyed`.
+pub struct DestroySlot<T, P: DropPolicy = DefaultPolicy> {
+    state: State<T, P>,
+}
+
+impl<T> DestroySlot<T> {
+    /// Create a new slot, armed with the given item
+    #[track_caller]
+    pub fn new(item: T) -> Self {
+        DestroySlot::from_guard(MustDestroy::new(item))
+    }
+}
+
+impl<T, P: DropPolicy> DestroySlot<T, P> {
+    /// Create a new, empty slot
+    pub fn empty() -> Self {
+        DestroySlot {
+            state: State::Empty,
+        }
+    }
+
+    /// Create a new slot, armed with an existing guard
+    pub fn from_guard(guard: MustDestroy<T, P>) -> Self {
+        DestroySlot {
+            state: State::Armed(Held::new(guard)),
+        }
+    }
+
+    /// Whether the slot holds an item that still has to be destroyed.
+    pub fn is_armed(&self) -> bool {
+        matches!(self.state, State::Armed(_))
+    }
+
+    /// Whether the slot's item was destroyed by `destroy_in_place`.
+    pub fn is_destroyed(&self) -> bool {
+        matches!(self.state, State::Destroyed)
+    }
+
+    /// Arms the slot with the given item, recording the caller as where its guard was
+    /// created. Hands back the guard that was already in the slot, if any.
+    #[track_caller]
+    pub fn fill(&mut self, item: T) -> Option<MustDestroy<T, P>> {
+        self.fill_guard(MustDestroy::arm(item, Origin::here::<T>()))
+    }
+
+    /// Arms the slot with an existing guard. Hands back the guard that was already in the
+    /// slot, if any.
+    pub fn fill_guard(&mut self, guard: MustDestroy<T, P>) -> Option<MustDestroy<T, P>> {
+        match std::mem::replace(&mut self.state, State::Armed(Held::new(guard))) {
+            State::Armed(held) => Some(held.into_guard()),
+            State::Empty | State::Destroyed => None,
+        }
+    }
+
+    /// Why the slot has no item, when it isn't armed.
+    fn missing(&self) -> SlotError {
+        let type_name = type_name::<T>();
+        if self.is_destroyed() {
+            SlotError::Destroyed { type_name }
+        } else {
+            SlotError::Empty { type_name }
+        }
+    }
+
+    /// Takes the guard out of the slot, leaving it empty.
+    pub fn take(&mut self) -> Result<MustDestroy<T, P>, SlotError> {
+        match std::mem::replace(&mut self.state, State::Empty) {
+            State::Armed(held) => Ok(held.into_guard()),
+            state => {
+                self.state = state;
+                Err(self.missing())
+            }
+        }
+    }
+
+    /// Destroys the slot's item, leaving the slot marked as destroyed.
+    pub fn destroy_in_place<Args>(&mut self, args: Args) -> Result<(), SlotError>
+    where
+        T: Destroy<Args>,
+    {
+        let guard = self.take()?;
+        self.state = State::Destroyed;
+        guard.destroy(args);
+        Ok(())
+    }
+
+    /// The slot's item.
+    pub fn get(&self) -> Result<&T, SlotError> {
+        match &self.state {
+            State::Armed(held) => Ok(held.get()),
+            _ => Err(self.missing()),
+        }
+    }
+
+    /// The slot's item.
+    pub fn get_mut(&mut self) -> Result<&mut T, SlotError> {
+        let missing = self.missing();
+        match &mut self.state {
+            State::Armed(held) => Ok(held.get_mut()),
+            _ => Err(missing),
+        }
+    }
+}
+
+impl<T, P: DropPolicy> Default for DestroySlot<T, P> {
+    fn default() -> Self {
+        DestroySlot::empty()
+    }
+}
+
+impl<Args, T: Destroy<Args>, P: DropPolicy> Destroy<Args> for DestroySlot<T, P> {
+    /// destroys the item if the slot is armed
+    fn destroy(mut self, args: Args) {
+        if let Ok(guard) = self.take() {
+            guard.destroy(args);
+        }
+    }
+}
+
+impl<T, P: DropPolicy> fmt::Debug for DestroySlot<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self.state {
+            State::Empty => "Empty",
+            State::Armed(_) => "Armed",
+            State::Destroyed => "Destroyed",
+        };
+        f.debug_tuple("DestroySlot").field(&state).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{scope, Destroy, DestroySlot, MustDestroy, Reason, SlotError};
+    use std::cell::Cell;
+
+    /// Counts how many times it was destroyed.
+    struct Socket<'a>(&'a Cell<u32>);
+
+    impl<'a> Destroy<()> for Socket<'a> {
+        fn destroy(self, _args: ()) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    /// Owns a socket it closes from a `&mut self` method.
+    struct Client<'a> {
+        socket: DestroySlot<Socket<'a>>,
+    }
+
+    impl<'a> Client<'a> {
+        fn close(&mut self) -> Result<(), SlotError> {
+            self.socket.destroy_in_place(())
+        }
+    }
+
+    #[test]
+    fn test_slot() {
+        let destroyed = Cell::new(0);
+        let mut client = Client {
+            socket: DestroySlot::new(Socket(&destroyed)),
+        };
+        assert!(client.socket.is_armed());
+        assert!(client.socket.get().is_ok());
+
+        client.close().unwrap();
+        assert_eq!(1, destroyed.get());
+        assert!(!client.socket.is_armed());
+        assert!(client.socket.is_destroyed());
+        assert!(matches!(client.close(), Err(SlotError::Destroyed { .. })));
+        assert!(matches!(
+            client.socket.get(),
+            Err(SlotError::Destroyed { .. })
+        ));
+        let error = client.socket.get().err().unwrap().to_string();
+        assert!(error.contains("Socket"));
+        assert!(error.contains("already destroyed"));
+
+        assert!(client.socket.fill(Socket(&destroyed)).is_none());
+        let guard = client.socket.take().ok().unwrap();
+        assert!(matches!(client.socket.take(), Err(SlotError::Empty { .. })));
+        assert!(client.socket.fill_guard(guard).is_none());
+        client.socket.destroy(());
+        assert_eq!(2, destroyed.get());
+    }
+
+    #[test]
+    fn test_refill_hands_back_guard() {
+        let destroyed = Cell::new(0);
+        let mut slot = DestroySlot::new(Socket(&destroyed));
+        let old = slot.fill_guard(MustDestroy::new(Socket(&destroyed)));
+        old.unwrap().destroy();
+        slot.destroy(());
+        assert_eq!(2, destroyed.get());
+    }
+
+    #[test]
+    fn test_dropping_armed_slot_is_violation() {
+        let destroyed = Cell::new(0);
+        let violations = scope(|s| {
+            let slot = DestroySlot::from_guard(s.guard(Socket(&destroyed)));
+            drop(slot);
+            let mut slot = DestroySlot::from_guard(s.guard(Socket(&destroyed)));
+            slot.destroy_in_place(()).unwrap();
+            drop(slot);
+        })
+        .unwrap_err();
+        assert_eq!(1, violations.len());
+        assert_eq!(Reason::Dropped, violations[0].reason());
+        assert_eq!(1, destroyed.get());
+    }
+}
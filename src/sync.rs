@@ -0,0 +1,15 @@
+//! Locking for the state shared by this crate's containers and bookkeeping.
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Locks `mutex`, even if a thread panicked while holding it.
+///
+/// Every update this crate makes under a lock leaves the state valid, so a poisoned lock is
+/// still usable.
+pub(crate) fn lock<S>(mutex: &Mutex<S>) -> MutexGuard<'_, S> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Takes the state out of `mutex`, even if it was poisoned, like `lock`.
+pub(crate) fn into_inner<S>(mutex: Mutex<S>) -> S {
+    mutex.into_inner().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,228 @@
+//! Destruction that can fail, handing the still armed guard back to the caller.
+use crate::{DefaultPolicy, DropPolicy, MustDestroy};
+use std::error::Error;
+use std::fmt;
+
+/// Trait applied to items whose destruction can fail.
+///
+/// `Args` works the same as it does for `Destroy`.
+pub trait TryDestroy<Args>: Sized {
+    /// The error produced when destruction fails.
+    type Error;
+
+    /// Attempts to destroy the item being called upon. On failure the item is handed back
+    /// along with the error, so it can still be destroyed another way.
+    fn try_destroy(self, args: Args) -> Result<(), (Self, Self::Error)>;
+}
+
+/// The error returned when destroying a `MustDestroy` fails.
+///
+/// It holds on to the guard, which is still armed and must be destroyed some other way.
+pub struct DestroyError<T, E, P: DropPolicy = DefaultPolicy> {
+    guard: MustDestroy<T, P>,
+    error: E,
+}
+
+impl<T, E, P: DropPolicy> DestroyError<T, E, P> {
+    /// The error that caused destruction to fail.
+    pub fn error(&self) -> &E {
+        &self.error
+    }
+
+    /// The guard that failed to be destroyed.
+    pub fn guard(&self) -> &MustDestroy<T, P> {
+        &self.guard
+    }
+
+    /// Mutable access to the guard that failed to be destroyed.
+    pub fn guard_mut(&mut self) -> &mut MustDestroy<T, P> {
+        &mut self.guard
+    }
+
+    /// Takes the guard back, discarding the error.
+    pub fn into_guard(self) -> MustDestroy<T, P> {
+        self.guard
+    }
+
+    /// Splits into the still armed guard and the error.
+    pub fn into_parts(self) -> (MustDestroy<T, P>, E) {
+        (self.guard, self.error)
+    }
+}
+
+impl<T, E: fmt::Debug, P: DropPolicy> fmt::Debug for DestroyError<T, E, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DestroyError")
+            .field("type_name", &std::any::type_name::<T>())
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<T, E: fmt::Display, P: DropPolicy> fmt::Display for DestroyError<T, E, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to destroy `{}`: {}",
+            std::any::type_name::<T>(),
+            self.error
+        )
+    }
+}
+
+impl<T, E: Error + 'static, P: DropPolicy> Error for DestroyError<T, E, P> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<T, P: DropPolicy> MustDestroy<T, P> {
+    /// Attempts to destroy and consume self and wrapped child.
+    ///
+    /// On failure the returned error holds the guard, still armed.
+    pub fn try_destroy<Args>(self, args: Args) -> Result<(), DestroyError<T, T::Error, P>>
+    where
+        T: TryDestroy<Args>,
+    {
+        TryDestroy::try_destroy(self, args).map_err(|(guard, error)| DestroyError { guard, error })
+    }
+
+    /// Attempts to destroy and consume self and wrapped child, calling `on_error` with the
+    /// still armed guard if that fails.
+    pub fn destroy_or_else<Args, F>(self, args: Args, on_error: F)
+    where
+        T: TryDestroy<Args>,
+        F: FnOnce(DestroyError<T, T::Error, P>),
+    {
+        if let Err(error) = self.try_destroy(args) {
+            on_error(error);
+        }
+    }
+
+    /// Attempts to destroy and consume self and wrapped child up to `attempts` times, giving
+    /// each attempt a clone of `args`.
+    ///
+    /// At least one attempt is always made. If every attempt fails, the error from the last
+    /// one is returned.
+    pub fn retry_destroy<Args: Clone>(
+        self,
+        attempts: usize,
+        args: Args,
+    ) -> Result<(), DestroyError<T, T::Error, P>>
+    where
+        T: TryDestroy<Args>,
+    {
+        let mut guard = self;
+        for _ in 1..attempts {
+            match guard.try_destroy(args.clone()) {
+                Ok(()) => return Ok(()),
+                Err(error) => guard = error.into_guard(),
+            }
+        }
+        guard.try_destroy(args)
+    }
+}
+
+impl<Args, T: TryDestroy<Args>, P: DropPolicy> TryDestroy<Args> for MustDestroy<T, P> {
+    type Error = T::Error;
+
+    /// attempts to destroy and consume self and wrapped child
+    fn try_destroy(self, args: Args) -> Result<(), (Self, Self::Error)> {
+        let (wrapped, origin, fallback) = self.disarm();
+        wrapped
+            .try_destroy(args)
+            .map_err(|(wrapped, error)| (MustDestroy::rearm(wrapped, origin, fallback), error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MustDestroy, TryDestroy};
+    use std::cell::Cell;
+
+    /// Fails to close until it has been asked enough times.
+    struct Flaky<'a> {
+        failures_left: usize,
+        closed: &'a Cell<bool>,
+    }
+
+    impl<'a> TryDestroy<&'a str> for Flaky<'a> {
+        type Error = String;
+
+        fn try_destroy(mut self, reason: &'a str) -> Result<(), (Self, String)> {
+            if self.failures_left > 0 {
+                self.failures_left -= 1;
+                return Err((self, format!("not yet: {}", reason)));
+            }
+            self.closed.set(true);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_try_destroy_hands_back_guard() {
+        let closed = Cell::new(false);
+        let guard = MustDestroy::new(Flaky {
+            failures_left: 1,
+            closed: &closed,
+        });
+
+        let error = guard.try_destroy("first").unwrap_err();
+        assert_eq!("not yet: first", error.error());
+        assert_eq!(0, error.guard().failures_left);
+        assert!(!closed.get());
+
+        error.into_guard().try_destroy("second").unwrap();
+        assert!(closed.get());
+    }
+
+    #[test]
+    fn test_destroy_or_else() {
+        let closed = Cell::new(false);
+        let escalated = Cell::new(false);
+        MustDestroy::new(Flaky {
+            failures_left: 1,
+            closed: &closed,
+        })
+        .destroy_or_else("shutdown", |error| {
+            escalated.set(true);
+            error.into_guard().try_destroy("escalated").unwrap();
+        });
+        assert!(escalated.get());
+        assert!(closed.get());
+    }
+
+    #[test]
+    fn test_retry_destroy() {
+        let closed = Cell::new(false);
+        let flaky = |failures_left| {
+            MustDestroy::new(Flaky {
+                failures_left,
+                closed: &closed,
+            })
+        };
+
+        let error = flaky(3).retry_destroy(3, "retry").unwrap_err();
+        assert_eq!(0, error.guard().failures_left);
+        assert!(!closed.get());
+        error.into_guard().try_destroy("last").unwrap();
+
+        closed.set(false);
+        flaky(2).retry_destroy(3, "retry").unwrap();
+        assert!(closed.get());
+    }
+
+    #[test]
+    fn test_handed_back_guard_keeps_fallback() {
+        let closed = Cell::new(false);
+        let guard = MustDestroy::with_fallback(
+            Flaky {
+                failures_left: 1,
+                closed: &closed,
+            },
+            |flaky| flaky.closed.set(true),
+        );
+        drop(guard.try_destroy("first").unwrap_err().into_guard());
+        assert!(closed.get());
+    }
+}
@@ -0,0 +1,212 @@
+//! Describes guards that were dropped without being destroyed.
+use crate::origin::Origin;
+use crate::DropPolicy;
+use crate::{catch, handler};
+#[cfg(feature = "backtrace")]
+use std::backtrace::Backtrace;
+use std::fmt;
+use std::panic::Location;
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "backtrace")]
+use std::sync::Arc;
+use std::thread;
+
+static VIOLATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of violations raised by any guard in this process so far.
+pub fn violation_count() -> usize {
+    VIOLATIONS.load(Ordering::Relaxed)
+}
+
+/// Why a violation was raised.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Reason {
+    /// The guard was dropped without being destroyed.
+    Dropped,
+    /// The guard was dropped without being destroyed while the thread was already
+    /// panicking, most likely because it was alive when something else panicked.
+    DroppedWhilePanicking,
+    /// The future destroying the guard was dropped before it completed.
+    Cancelled,
+    /// The guard was neither destroyed nor escaped by the time its scope ended, most likely
+    /// because it was leaked or forgotten.
+    Leaked,
+    /// The guard was still checked out of its `DestroyPool` when the pool was destroyed.
+    CheckedOut,
+    /// The guard was dropped without being destroyed, so its fallback destructor was run.
+    Fallback,
+}
+
+/// A guard that was dropped without being destroyed.
+///
+/// This is handed to the guard's `DropPolicy` to act upon.
+#[derive(Clone, Debug)]
+pub struct Violation {
+    type_name: &'static str,
+    location: &'static Location<'static>,
+    reason: Reason,
+    #[cfg(feature = "backtrace")]
+    backtrace: Arc<Backtrace>,
+}
+
+impl Violation {
+    /// Creates a violation for the guard created at `origin`.
+    pub(crate) fn new(origin: &Origin, reason: Reason) -> Self {
+        Violation {
+            type_name: origin.type_name(),
+            location: origin.location(),
+            reason,
+            #[cfg(feature = "backtrace")]
+            backtrace: origin.backtrace().clone(),
+        }
+    }
+
+    /// Creates a violation for the guard created at `origin`, that is being dropped now.
+    pub(crate) fn dropped(origin: &Origin) -> Self {
+        if thread::panicking() {
+            Violation::new(origin, Reason::DroppedWhilePanicking)
+        } else {
+            Violation::new(origin, Reason::Dropped)
+        }
+    }
+
+    /// Counts the violation towards `violation_count`, and passes it to the violation
+    /// handler.
+    pub(crate) fn record(&self) {
+        VIOLATIONS.fetch_add(1, Ordering::Relaxed);
+        handler::notify(self);
+    }
+
+    /// Records the violation and hands it to the policy `P`, unless it's caught by
+    /// `catch_violations`.
+    pub(crate) fn raise<P: DropPolicy>(&self) {
+        self.record();
+        self.enforce::<P>();
+    }
+
+    /// Records every violation, then hands each to the policy `P`, unless it's caught by
+    /// `catch_violations`.
+    ///
+    /// Recording them all first means none go uncounted or unseen by the violation handler
+    /// when the policy panics on the first.
+    pub(crate) fn raise_all<P: DropPolicy>(violations: Vec<Violation>) {
+        for violation in &violations {
+            violation.record();
+        }
+        for violation in &violations {
+            violation.enforce::<P>();
+        }
+    }
+
+    /// Hands the violation to the policy `P`, unless it's caught by `catch_violations`.
+    fn enforce<P: DropPolicy>(&self) {
+        if catch::catch(self) {
+            return;
+        }
+        if thread::panicking() {
+            P::on_violation_while_panicking(self);
+        } else {
+            P::on_violation(self);
+        }
+    }
+
+    /// The name of the type wrapped by the guard that was dropped.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Where the guard was created.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// Why the violation was raised.
+    pub fn reason(&self) -> Reason {
+        self.reason
+    }
+
+    /// The backtrace captured when the guard was created.
+    #[cfg(feature = "backtrace")]
+    pub fn backtrace(&self) -> &Backtrace {
+        &self.backtrace
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (type_name, location) = (self.type_name, self.location);
+        match self.reason {
+            Reason::Dropped => write!(
+                f,
+                "Can not drop `{}` created at {}, must call destroy.",
+                type_name, location
+            ),
+            Reason::DroppedWhilePanicking => write!(
+                f,
+                "`{}` created at {} was dropped while panicking, without calling destroy.",
+                type_name, location
+            ),
+            Reason::Cancelled => write!(
+                f,
+                "The future destroying `{}` created at {} was dropped before it completed.",
+                type_name, location
+            ),
+            Reason::Leaked => write!(
+                f,
+                "`{}` created at {} was leaked by the end of its scope, without calling destroy.",
+                type_name, location
+            ),
+            Reason::CheckedOut => write!(
+                f,
+                "`{}` created at {} was still checked out when its pool was destroyed.",
+                type_name, location
+            ),
+            Reason::Fallback => write!(
+                f,
+                "`{}` created at {} was dropped without calling destroy, so its fallback was run.",
+                type_name, location
+            ),
+        }?;
+        #[cfg(feature = "backtrace")]
+        write!(f, "\nCreated at:\n{}", self.backtrace)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{catch_violations, scope, MustDestroy};
+    use std::mem;
+
+    struct Reported;
+
+    #[test]
+    fn test_display_depends_on_reason() {
+        let dropped = catch_violations(|| drop(MustDestroy::new(Reported))).unwrap_err();
+        let message = dropped[0].to_string();
+        assert!(message.starts_with("Can not drop `"));
+        assert!(message.contains("Reported` created at src"));
+
+        let leaked = scope(|s| mem::forget(s.guard(Reported))).unwrap_err();
+        assert!(leaked[0]
+            .to_string()
+            .contains("was leaked by the end of its scope"));
+    }
+
+    #[test]
+    #[cfg(feature = "backtrace")]
+    fn test_backtrace() {
+        use std::backtrace::BacktraceStatus;
+
+        struct Traced;
+
+        let violations = catch_violations(|| drop(MustDestroy::new(Traced))).unwrap_err();
+        let backtrace = violations[0].backtrace();
+        assert_eq!(BacktraceStatus::Captured, backtrace.status());
+        let backtrace = backtrace.to_string();
+        assert!(backtrace.contains("test_backtrace"));
+        let message = violations[0].to_string();
+        assert!(message.contains(&format!("\nCreated at:\n{}", backtrace)));
+    }
+}